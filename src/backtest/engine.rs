@@ -0,0 +1,329 @@
+use crate::config;
+use crate::base::{Bar, read_trades};
+use crate::ch2::time_bar::compute_time_bars;
+use crate::ch10::bet_sizing::{build_cusum_bet_events, compute_bet_sizes, return_std};
+use std::collections::VecDeque;
+use std::error::Error;
+use chrono::{DateTime, Utc};
+use plotters::prelude::*;
+use serde::Serialize;
+
+#[derive(Debug, Clone)]
+pub struct BacktestConfig {
+    pub initial_capital: f64,
+    pub fee_rate: f64,      // fraction of traded notional
+    pub slippage_bps: f64,  // adverse price move per fill, in basis points
+    pub latency_bars: usize, // 0: fill at the signal bar close, n: fill at the open n bars later
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        BacktestConfig {
+            initial_capital: 1_000_000.0,
+            fee_rate: 0.0004,
+            slippage_bps: 1.0,
+            latency_bars: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Fill {
+    pub time: DateTime<Utc>,
+    pub quantity: f64, // signed, positive = buy
+    pub price: f64,    // after slippage
+    pub decision_price: f64, // bar close when the order was decided
+    pub fee: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EquityPoint {
+    pub time: DateTime<Utc>,
+    pub close: f64,
+    pub position: f64, // units held at the bar close
+    pub cash: f64,
+    pub equity: f64,
+}
+
+// Round trip from flat (or a position flip) back to flat
+#[derive(Debug, Clone, Serialize)]
+pub struct RoundTrip {
+    pub entry_time: DateTime<Utc>,
+    pub exit_time: DateTime<Utc>,
+    pub side: f64,        // 1 long, -1 short
+    pub quantity: f64,    // units bought (long) or sold (short) to build the position
+    pub entry_price: f64, // average price of the fills that increased the position
+    pub exit_price: f64,  // average price of the fills that reduced it
+    pub gross_pnl: f64,
+    pub fees: f64,
+    pub pnl: f64,         // gross_pnl - fees
+}
+
+#[derive(Debug, Clone)]
+pub struct BacktestResult {
+    pub equity_curve: Vec<EquityPoint>,
+    pub fills: Vec<Fill>,
+    pub trades: Vec<RoundTrip>, // closed round trips; a position still open at the end is left out
+}
+
+struct Order {
+    fill_index: usize,
+    target: f64, // fraction of equity
+    decision_price: f64,
+}
+
+// Event-driven simulation over bars.
+// targets[i] is the desired exposure (fraction of equity, e.g. from bet sizing)
+// decided at the close of bars[i]. A new order is sent only when the target changes.
+pub fn run_backtest_on_bars(bars: &[Bar], targets: &[f64], cfg: &BacktestConfig) -> BacktestResult {
+    let mut equity_curve = Vec::with_capacity(bars.len());
+    let mut fills = Vec::new();
+    let mut pending: VecDeque<Order> = VecDeque::new();
+
+    let mut cash = cfg.initial_capital;
+    let mut position = 0.0;
+    let mut last_target = 0.0;
+
+    let slippage = cfg.slippage_bps / 10_000.0;
+
+    let mut execute = |order: &Order, time: DateTime<Utc>, price: f64, cash: &mut f64, position: &mut f64| {
+        let equity = *cash + *position * price;
+        let desired = order.target * equity / price;
+        let quantity = desired - *position;
+        if quantity.abs() < 1e-12 {
+            return;
+        }
+        let fill_price = if quantity > 0.0 { price * (1.0 + slippage) } else { price * (1.0 - slippage) };
+        let fee = (quantity * fill_price).abs() * cfg.fee_rate;
+        *cash -= quantity * fill_price + fee;
+        *position += quantity;
+        fills.push(Fill { time, quantity, price: fill_price, decision_price: order.decision_price, fee });
+    };
+
+    for (i, bar) in bars.iter().enumerate() {
+        // 1. Fill orders that arrive at this bar's open
+        while pending.front().is_some_and(|o| o.fill_index == i) {
+            let order = pending.pop_front().unwrap();
+            execute(&order, bar.time, bar.open, &mut cash, &mut position);
+        }
+
+        // 2. New signal at the bar close
+        let target = targets.get(i).copied().unwrap_or(0.0);
+        if target != last_target {
+            last_target = target;
+            let order = Order { fill_index: i + cfg.latency_bars, target, decision_price: bar.close };
+            if cfg.latency_bars == 0 {
+                execute(&order, bar.time, bar.close, &mut cash, &mut position);
+            } else if order.fill_index < bars.len() {
+                pending.push_back(order);
+            }
+        }
+
+        // 3. Mark to market
+        equity_curve.push(EquityPoint {
+            time: bar.time,
+            close: bar.close,
+            position,
+            cash,
+            equity: cash + position * bar.close,
+        });
+    }
+
+    let trades = round_trips(&fills);
+    BacktestResult { equity_curve, fills, trades }
+}
+
+// Pairs fills into round trips. A fill that flips the position is split: the part that
+// closes the open trade and the rest that opens the next one, fee pro rata.
+pub fn round_trips(fills: &[Fill]) -> Vec<RoundTrip> {
+    struct Open {
+        entry_time: DateTime<Utc>,
+        side: f64,
+        entry_qty: f64,
+        entry_notional: f64,
+        exit_qty: f64,
+        exit_notional: f64,
+        fees: f64,
+    }
+    let mut trades = Vec::new();
+    let mut open: Option<Open> = None;
+    let mut position: f64 = 0.0;
+    let eps = 1e-12;
+
+    for fill in fills {
+        let mut remaining = fill.quantity;
+        while remaining.abs() > eps {
+            let trade = open.get_or_insert_with(|| Open {
+                entry_time: fill.time,
+                side: remaining.signum(),
+                entry_qty: 0.0,
+                entry_notional: 0.0,
+                exit_qty: 0.0,
+                exit_notional: 0.0,
+                fees: 0.0,
+            });
+            // Increase the position, or reduce it by at most its size
+            let part = if remaining.signum() == trade.side {
+                remaining
+            } else {
+                remaining.signum() * remaining.abs().min(position.abs())
+            };
+            let fee = fill.fee * (part / fill.quantity).abs();
+            trade.fees += fee;
+            if part.signum() == trade.side {
+                trade.entry_qty += part.abs();
+                trade.entry_notional += part.abs() * fill.price;
+            } else {
+                trade.exit_qty += part.abs();
+                trade.exit_notional += part.abs() * fill.price;
+            }
+            position += part;
+            remaining -= part;
+
+            if position.abs() <= eps {
+                let trade = open.take().unwrap();
+                let entry_price = trade.entry_notional / trade.entry_qty;
+                let exit_price = trade.exit_notional / trade.exit_qty;
+                let gross_pnl = trade.side * (trade.exit_notional - trade.entry_notional);
+                trades.push(RoundTrip {
+                    entry_time: trade.entry_time,
+                    exit_time: fill.time,
+                    side: trade.side,
+                    quantity: trade.entry_qty,
+                    entry_price,
+                    exit_price,
+                    gross_pnl,
+                    fees: trade.fees,
+                    pnl: gross_pnl - trade.fees,
+                });
+                position = 0.0;
+            }
+        }
+    }
+    trades
+}
+
+pub fn write_backtest_csv(result: &BacktestResult, equity_path: &str, fills_path: &str, trades_path: &str) -> Result<(), Box<dyn Error>> {
+    let mut wtr = csv::Writer::from_path(equity_path)?;
+    for point in &result.equity_curve {
+        wtr.serialize(point)?;
+    }
+    wtr.flush()?;
+
+    let mut wtr = csv::Writer::from_path(fills_path)?;
+    for fill in &result.fills {
+        wtr.serialize(fill)?;
+    }
+    wtr.flush()?;
+
+    let mut wtr = csv::Writer::from_path(trades_path)?;
+    for trade in &result.trades {
+        wtr.serialize(trade)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+pub fn run_backtest() -> Result<(), Box<dyn Error>> {
+    let file_path = config::TARDIS_CSV_PATH;
+    println!("Reading trades from {}...", file_path);
+    let trades = read_trades(file_path)?;
+    println!("Read {} trades.", trades.len());
+
+    // 1. Compute Time Bars (15 minutes)
+    let time_interval_minutes = 15;
+    println!("Computing {} minute time bars...", time_interval_minutes);
+    let bars = compute_time_bars(&trades, time_interval_minutes);
+    println!("Generated {} time bars.", bars.len());
+
+    // 2. Target positions from CUSUM bet sizing (ch10)
+    let h = return_std(&bars);
+    let events = build_cusum_bet_events(&bars, h, 4);
+    let targets = compute_bet_sizes(&bars, &events, 2, 0.05);
+    println!("Built {} bet events (h = {:.6}).", events.len(), h);
+
+    // 3. Simulate
+    let cfg = BacktestConfig::default();
+    println!(
+        "Simulating with fee = {:.4}%, slippage = {:.1} bps, latency = {} bar(s)...",
+        cfg.fee_rate * 100.0, cfg.slippage_bps, cfg.latency_bars
+    );
+    let result = run_backtest_on_bars(&bars, &targets, &cfg);
+    let final_equity = result.equity_curve.last().map(|p| p.equity).unwrap_or(cfg.initial_capital);
+    let total_fees: f64 = result.fills.iter().map(|f| f.fee).sum();
+    println!("Fills: {}, total fees: {:.2}", result.fills.len(), total_fees);
+    let winners = result.trades.iter().filter(|t| t.pnl > 0.0).count();
+    println!("Round trips: {} ({} winning)", result.trades.len(), winners);
+    println!(
+        "Final equity: {:.2} ({:+.2}%)",
+        final_equity, (final_equity / cfg.initial_capital - 1.0) * 100.0
+    );
+
+    // 4. Save results
+    std::fs::create_dir_all("src/backtest/result")?;
+    let equity_csv = "src/backtest/result/equity_curve.csv";
+    let fills_csv = "src/backtest/result/fills.csv";
+    let trades_csv = "src/backtest/result/trades.csv";
+    write_backtest_csv(&result, equity_csv, fills_csv, trades_csv)?;
+    println!("Results saved to {}, {} and {}", equity_csv, fills_csv, trades_csv);
+
+    let output_path = "src/backtest/result/equity_curve.png";
+    println!("Drawing chart to {}...", output_path);
+    draw_equity_chart(&result, output_path)?;
+    println!("Chart saved to {}", output_path);
+
+    // Open file
+    #[cfg(target_os = "windows")]
+    std::process::Command::new("cmd")
+        .args(["/C", "start", output_path])
+        .spawn()?;
+
+    #[cfg(target_os = "macos")]
+    std::process::Command::new("open")
+        .arg(output_path)
+        .spawn()?;
+
+    #[cfg(target_os = "linux")]
+    std::process::Command::new("xdg-open")
+        .arg(output_path)
+        .spawn()?;
+
+    Ok(())
+}
+
+fn draw_equity_chart(result: &BacktestResult, output_path: &str) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(output_path, (1280, 900)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let (upper, lower) = root.split_vertically(600);
+
+    let curve = &result.equity_curve;
+    let min_time = curve.first().unwrap().time;
+    let max_time = curve.last().unwrap().time;
+
+    let min_equity = curve.iter().map(|p| p.equity).fold(f64::INFINITY, f64::min);
+    let max_equity = curve.iter().map(|p| p.equity).fold(f64::NEG_INFINITY, f64::max);
+    let pad = ((max_equity - min_equity) * 0.05).max(1.0);
+
+    let mut equity_chart = ChartBuilder::on(&upper)
+        .caption("Backtest Equity Curve", ("sans-serif", 30).into_font())
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(80)
+        .build_cartesian_2d(min_time..max_time, (min_equity - pad)..(max_equity + pad))?;
+
+    equity_chart.configure_mesh().draw()?;
+    equity_chart.draw_series(LineSeries::new(curve.iter().map(|p| (p.time, p.equity)), &BLUE))?;
+
+    let max_notional = curve.iter().map(|p| (p.position * p.close).abs()).fold(0.0, f64::max).max(1.0);
+
+    let mut position_chart = ChartBuilder::on(&lower)
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(80)
+        .build_cartesian_2d(min_time..max_time, -max_notional * 1.1..max_notional * 1.1)?;
+
+    position_chart.configure_mesh().y_desc("Position Notional").draw()?;
+    position_chart.draw_series(LineSeries::new(curve.iter().map(|p| (p.time, p.position * p.close)), &BLACK))?;
+
+    Ok(())
+}
@@ -0,0 +1,3 @@
+pub mod engine;
+
+pub use engine::run_backtest;
@@ -0,0 +1,115 @@
+use std::error::Error;
+use std::path::Path;
+use serde::Deserialize;
+
+// Top of book (Tardis `quotes` dataset)
+#[derive(Debug, Clone, Deserialize)]
+pub struct Quote {
+    pub exchange: String,
+    pub symbol: String,
+    pub timestamp: u64, // microseconds
+    pub local_timestamp: u64,
+    pub ask_amount: f64,
+    pub ask_price: f64,
+    pub bid_price: f64,
+    pub bid_amount: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Level {
+    pub price: f64,
+    pub amount: f64,
+}
+
+// Order book snapshot (Tardis `book_snapshot_N` datasets); bids best first (descending),
+// asks best first (ascending)
+#[derive(Debug, Clone)]
+pub struct BookSnapshot {
+    pub exchange: String,
+    pub symbol: String,
+    pub timestamp: u64, // microseconds
+    pub local_timestamp: u64,
+    pub bids: Vec<Level>,
+    pub asks: Vec<Level>,
+}
+
+impl BookSnapshot {
+    pub fn best_bid(&self) -> Option<Level> {
+        self.bids.first().copied()
+    }
+
+    pub fn best_ask(&self) -> Option<Level> {
+        self.asks.first().copied()
+    }
+}
+
+impl From<&Quote> for BookSnapshot {
+    fn from(q: &Quote) -> Self {
+        BookSnapshot {
+            exchange: q.exchange.clone(),
+            symbol: q.symbol.clone(),
+            timestamp: q.timestamp,
+            local_timestamp: q.local_timestamp,
+            bids: vec![Level { price: q.bid_price, amount: q.bid_amount }],
+            asks: vec![Level { price: q.ask_price, amount: q.ask_amount }],
+        }
+    }
+}
+
+pub fn read_quotes<P: AsRef<Path>>(path: P) -> Result<Vec<Quote>, Box<dyn Error>> {
+    let mut rdr = csv::Reader::from_path(path)?;
+    let mut quotes = Vec::new();
+    for result in rdr.deserialize() {
+        // Tardis leaves one side empty when it is missing; skip those rows
+        let quote: Quote = match result {
+            Ok(q) => q,
+            Err(e) if matches!(e.kind(), csv::ErrorKind::Deserialize { .. }) => continue,
+            Err(e) => return Err(e.into()),
+        };
+        quotes.push(quote);
+    }
+    Ok(quotes)
+}
+
+// Columns asks[i].price / asks[i].amount / bids[i].price / bids[i].amount for every level in the header
+pub fn read_book_snapshots<P: AsRef<Path>>(path: P) -> Result<Vec<BookSnapshot>, Box<dyn Error>> {
+    let mut rdr = csv::Reader::from_path(path)?;
+    let headers = rdr.headers()?.clone();
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let required = |name: &str| column(name).ok_or_else(|| format!("Missing column {}", name));
+
+    let exchange = required("exchange")?;
+    let symbol = required("symbol")?;
+    let timestamp = required("timestamp")?;
+    let local_timestamp = required("local_timestamp")?;
+    let side_columns = |side: &str| -> Vec<(usize, usize)> {
+        (0..)
+            .map_while(|i| Some((column(&format!("{}[{}].price", side, i))?, column(&format!("{}[{}].amount", side, i))?)))
+            .collect()
+    };
+    let bid_columns = side_columns("bids");
+    let ask_columns = side_columns("asks");
+
+    let mut snapshots = Vec::new();
+    for result in rdr.records() {
+        let record = result?;
+        let levels = |columns: &[(usize, usize)]| -> Vec<Level> {
+            columns.iter()
+                .map_while(|&(p, a)| {
+                    let price = record.get(p)?.parse().ok()?;
+                    let amount = record.get(a)?.parse().ok()?;
+                    Some(Level { price, amount })
+                })
+                .collect()
+        };
+        snapshots.push(BookSnapshot {
+            exchange: record[exchange].to_string(),
+            symbol: record[symbol].to_string(),
+            timestamp: record[timestamp].parse()?,
+            local_timestamp: record[local_timestamp].parse()?,
+            bids: levels(&bid_columns),
+            asks: levels(&ask_columns),
+        });
+    }
+    Ok(snapshots)
+}
@@ -0,0 +1,105 @@
+use crate::config;
+use std::error::Error;
+use std::path::Path;
+use std::sync::OnceLock;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FxRate {
+    pub timestamp: u64, // microseconds
+    pub rate: f64,      // units of `quote` per unit of `base`
+}
+
+// Price of `base` in `quote` over time, sorted by timestamp
+#[derive(Debug, Clone)]
+pub struct FxSeries {
+    pub base: String,
+    pub quote: String,
+    pub rates: Vec<FxRate>,
+}
+
+impl FxSeries {
+    // CSV with `timestamp` and `rate` columns
+    pub fn load<P: AsRef<Path>>(base: &str, quote: &str, path: P) -> Result<Self, Box<dyn Error>> {
+        let mut rdr = csv::Reader::from_path(path)?;
+        let mut rates = Vec::new();
+        for result in rdr.deserialize() {
+            let rate: FxRate = result?;
+            rates.push(rate);
+        }
+        rates.sort_by_key(|r| r.timestamp);
+        Ok(FxSeries { base: base.to_string(), quote: quote.to_string(), rates })
+    }
+
+    // As-of join: the last rate at or before timestamp; None before the first observation
+    pub fn rate_at(&self, timestamp: u64) -> Option<f64> {
+        let i = self.rates.partition_point(|r| r.timestamp <= timestamp);
+        if i > 0 { Some(self.rates[i - 1].rate) } else { None }
+    }
+}
+
+// Converts amounts into the reference currency, directly or through one intermediate currency
+#[derive(Debug, Clone, Default)]
+pub struct CurrencyConverter {
+    pub reference: String,
+    pub series: Vec<FxSeries>,
+}
+
+impl CurrencyConverter {
+    pub fn new(reference: &str, series: Vec<FxSeries>) -> Self {
+        CurrencyConverter { reference: reference.to_string(), series }
+    }
+
+    // Units of `to` per unit of `from` from one series, used as is or inverted
+    fn direct_rate(&self, from: &str, to: &str, timestamp: u64) -> Option<f64> {
+        self.series.iter().find_map(|s| {
+            if s.base == from && s.quote == to {
+                s.rate_at(timestamp)
+            } else if s.base == to && s.quote == from {
+                s.rate_at(timestamp).filter(|r| *r != 0.0).map(|r| 1.0 / r)
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn rate(&self, from: &str, to: &str, timestamp: u64) -> Option<f64> {
+        if from == to {
+            return Some(1.0);
+        }
+        self.direct_rate(from, to, timestamp).or_else(|| {
+            self.series.iter()
+                .flat_map(|s| [s.base.as_str(), s.quote.as_str()])
+                .filter(|via| *via != from && *via != to)
+                .find_map(|via| Some(self.direct_rate(from, via, timestamp)? * self.direct_rate(via, to, timestamp)?))
+        })
+    }
+
+    pub fn can_convert(&self, currency: &str) -> bool {
+        currency.is_empty() || self.rate(currency, &self.reference, u64::MAX).is_some()
+    }
+
+    // Amount in the reference currency; unchanged when the currency is unknown ("", taken
+    // to be the reference currency). None when no rate is known as of the timestamp.
+    pub fn convert(&self, amount: f64, currency: &str, timestamp: u64) -> Option<f64> {
+        if currency.is_empty() || currency == self.reference {
+            return Some(amount);
+        }
+        self.rate(currency, &self.reference, timestamp).map(|r| amount * r)
+    }
+}
+
+// Converter into config::REFERENCE_CURRENCY from the series in config::FX_SERIES, loaded once
+pub fn converter() -> &'static CurrencyConverter {
+    static CONVERTER: OnceLock<CurrencyConverter> = OnceLock::new();
+    CONVERTER.get_or_init(|| {
+        let mut series = Vec::new();
+        for (base, quote, path) in config::FX_SERIES {
+            match FxSeries::load(base, quote, path) {
+                Ok(s) => series.push(s),
+                Err(e) => eprintln!("Could not load {}/{} rates from {}: {}", base, quote, path, e),
+            }
+        }
+        CurrencyConverter::new(config::REFERENCE_CURRENCY, series)
+    })
+}
@@ -0,0 +1,153 @@
+use crate::config;
+use crate::base::Trade;
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::Path;
+use std::sync::OnceLock;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContractType {
+    Linear,  // amount in base units (e.g. Binance BTCUSDT)
+    Inverse, // amount in contracts worth `multiplier` quote units each (e.g. BitMEX XBTUSD)
+    Quanto,  // contract worth price * multiplier settlement units (e.g. BitMEX ETHUSD)
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Instrument {
+    pub exchange: String,
+    pub symbol: String,
+    pub contract_type: ContractType,
+    #[serde(default = "default_multiplier")]
+    pub multiplier: f64,
+    pub tick_size: f64,
+    pub lot_size: f64,
+    pub quote_currency: String,
+    #[serde(default)]
+    pub settle_currency: Option<String>, // defaults to the quote currency
+}
+
+fn default_multiplier() -> f64 {
+    1.0
+}
+
+impl Instrument {
+    // Linear contract with multiplier 1, assumed for instruments missing from the registry
+    pub fn linear(exchange: &str, symbol: &str) -> Self {
+        Instrument {
+            exchange: exchange.to_string(),
+            symbol: symbol.to_string(),
+            contract_type: ContractType::Linear,
+            multiplier: 1.0,
+            tick_size: 0.0,
+            lot_size: 0.0,
+            quote_currency: String::new(),
+            settle_currency: None,
+        }
+    }
+
+    // Currency of notional(): the quote currency, or the settlement currency of quanto contracts
+    pub fn notional_currency(&self) -> &str {
+        match self.contract_type {
+            ContractType::Quanto => self.settle_currency.as_deref().unwrap_or(&self.quote_currency),
+            _ => &self.quote_currency,
+        }
+    }
+
+    // Traded value of `amount` at `price`, in notional_currency()
+    pub fn notional(&self, price: f64, amount: f64) -> f64 {
+        match self.contract_type {
+            ContractType::Linear | ContractType::Quanto => price * amount * self.multiplier,
+            ContractType::Inverse => amount * self.multiplier,
+        }
+    }
+
+    // Traded quantity of the underlying (base units; contracts for quanto)
+    pub fn volume(&self, price: f64, amount: f64) -> f64 {
+        match self.contract_type {
+            ContractType::Linear => amount * self.multiplier,
+            ContractType::Inverse => if price > 0.0 { amount * self.multiplier / price } else { 0.0 },
+            ContractType::Quanto => amount,
+        }
+    }
+
+    pub fn round_price(&self, price: f64) -> f64 {
+        if self.tick_size > 0.0 { (price / self.tick_size).round() * self.tick_size } else { price }
+    }
+
+    pub fn round_amount(&self, amount: f64) -> f64 {
+        if self.lot_size > 0.0 { (amount / self.lot_size).round() * self.lot_size } else { amount }
+    }
+}
+
+// Instruments keyed by exchange, then symbol (the Tardis `exchange` / `symbol` columns)
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentRegistry {
+    instruments: HashMap<String, HashMap<String, Instrument>>,
+}
+
+impl InstrumentRegistry {
+    // JSON array of instruments
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let text = std::fs::read_to_string(path)?;
+        let list: Vec<Instrument> = serde_json::from_str(&text)?;
+        let mut registry = InstrumentRegistry::default();
+        for instrument in list {
+            registry.insert(instrument);
+        }
+        Ok(registry)
+    }
+
+    pub fn insert(&mut self, instrument: Instrument) {
+        self.instruments
+            .entry(instrument.exchange.clone())
+            .or_default()
+            .insert(instrument.symbol.clone(), instrument);
+    }
+
+    pub fn get(&self, exchange: &str, symbol: &str) -> Option<&Instrument> {
+        self.instruments.get(exchange).and_then(|m| m.get(symbol))
+    }
+
+    // Registered instrument of the trade, or a linear one
+    pub fn resolve(&self, trade: &Trade) -> Instrument {
+        self.get(&trade.exchange, &trade.symbol)
+            .cloned()
+            .unwrap_or_else(|| Instrument::linear(&trade.exchange, &trade.symbol))
+    }
+
+    pub fn notional(&self, trade: &Trade) -> f64 {
+        self.notional_in(trade).0
+    }
+
+    // Notional and its currency ("" for unregistered instruments)
+    pub fn notional_in(&self, trade: &Trade) -> (f64, &str) {
+        match self.get(&trade.exchange, &trade.symbol) {
+            Some(instrument) => (instrument.notional(trade.price, trade.amount), instrument.notional_currency()),
+            None => (trade.price * trade.amount, ""),
+        }
+    }
+
+    pub fn volume(&self, trade: &Trade) -> f64 {
+        match self.get(&trade.exchange, &trade.symbol) {
+            Some(instrument) => instrument.volume(trade.price, trade.amount),
+            None => trade.amount,
+        }
+    }
+}
+
+// Registry loaded once from config::INSTRUMENTS_PATH; empty (all linear) when the file is missing
+pub fn registry() -> &'static InstrumentRegistry {
+    static REGISTRY: OnceLock<InstrumentRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let path = config::INSTRUMENTS_PATH;
+        if !Path::new(path).exists() {
+            return InstrumentRegistry::default();
+        }
+        InstrumentRegistry::load(path).unwrap_or_else(|e| {
+            eprintln!("Could not load instruments from {}: {}", path, e);
+            InstrumentRegistry::default()
+        })
+    })
+}
@@ -0,0 +1,67 @@
+// Eigen-decomposition of a symmetric matrix by cyclic Jacobi rotations.
+// Returns eigenvalues in descending order and the matching eigenvectors as columns
+// (vectors[i][k] is component i of eigenvector k).
+pub fn symmetric_eigen(matrix: &[Vec<f64>]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let n = matrix.len();
+    let mut a: Vec<Vec<f64>> = matrix.to_vec();
+    let mut v: Vec<Vec<f64>> = (0..n).map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect()).collect();
+
+    for _sweep in 0..100 {
+        let off: f64 = (0..n).flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j] * a[i][j])
+            .sum();
+        if off < 1e-22 {
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[p][q].abs() < 1e-300 {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+
+                for row in a.iter_mut() {
+                    let (akp, akq) = (row[p], row[q]);
+                    row[p] = c * akp - s * akq;
+                    row[q] = s * akp + c * akq;
+                }
+                let (row_p, row_q) = (a[p].clone(), a[q].clone());
+                a[p] = row_p.iter().zip(&row_q).map(|(apk, aqk)| c * apk - s * aqk).collect();
+                a[q] = row_p.iter().zip(&row_q).map(|(apk, aqk)| s * apk + c * aqk).collect();
+                for row in v.iter_mut() {
+                    let (vkp, vkq) = (row[p], row[q]);
+                    row[p] = c * vkp - s * vkq;
+                    row[q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|&i, &j| a[j][j].total_cmp(&a[i][i]));
+    let values = order.iter().map(|&k| a[k][k]).collect();
+    let vectors = (0..n).map(|i| order.iter().map(|&k| v[i][k]).collect()).collect();
+    (values, vectors)
+}
+
+// Sample covariance matrix of the columns of x (one row per observation)
+pub fn covariance_matrix(x: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let n = x.len();
+    let m = x.first().map(|r| r.len()).unwrap_or(0);
+    let means: Vec<f64> = (0..m).map(|j| x.iter().map(|r| r[j]).sum::<f64>() / n as f64).collect();
+    let mut cov = vec![vec![0.0; m]; m];
+    for row in x {
+        for (i, cov_row) in cov.iter_mut().enumerate() {
+            for (j, c) in cov_row.iter_mut().enumerate() {
+                *c += (row[i] - means[i]) * (row[j] - means[j]);
+            }
+        }
+    }
+    let denom = (n as f64 - 1.0).max(1.0);
+    cov.iter_mut().flatten().for_each(|c| *c /= denom);
+    cov
+}
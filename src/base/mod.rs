@@ -1,10 +1,17 @@
+pub mod book;
+pub mod fx;
+pub mod instrument;
+pub mod linalg;
+pub mod order_book;
+pub mod stats;
+
 use std::error::Error;
 use std::path::Path;
 use serde::Deserialize;
 use chrono::{DateTime, Utc};
 use plotters::prelude::*;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Trade {
     pub exchange: String,
     pub symbol: String,
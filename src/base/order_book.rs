@@ -0,0 +1,275 @@
+use crate::base::book::{BookSnapshot, Level};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::path::Path;
+use serde::Deserialize;
+
+// One row of the Tardis `incremental_book_L2` dataset. `amount` is the new total at the
+// price level (0 removes it), not a delta.
+#[derive(Debug, Clone, Deserialize)]
+pub struct L2Update {
+    pub exchange: String,
+    pub symbol: String,
+    pub timestamp: u64, // microseconds
+    pub local_timestamp: u64,
+    pub is_snapshot: bool,
+    pub side: String, // "bid" or "ask"
+    pub price: f64,
+    pub amount: f64,
+}
+
+pub fn read_l2_updates<P: AsRef<Path>>(path: P) -> Result<Vec<L2Update>, Box<dyn Error>> {
+    let mut rdr = csv::Reader::from_path(path)?;
+    let mut updates = Vec::new();
+    for result in rdr.deserialize() {
+        let update: L2Update = result?;
+        updates.push(update);
+    }
+    Ok(updates)
+}
+
+// Per-exchange replay rules
+#[derive(Debug, Clone, Copy)]
+pub struct BookSemantics {
+    // Drop levels on the opposite side that an update crosses. Binance diff streams can leave
+    // stale levels behind that are never removed explicitly.
+    pub uncross: bool,
+    // Silence between updates longer than this is reported as a possible gap, microseconds
+    pub max_silence: u64,
+}
+
+impl BookSemantics {
+    pub fn for_exchange(exchange: &str) -> Self {
+        match exchange {
+            "binance" | "binance-futures" | "binance-delivery" | "binance-us" => {
+                BookSemantics { uncross: true, max_silence: 10_000_000 }
+            }
+            _ => BookSemantics { uncross: false, max_silence: 60_000_000 },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BookIssueKind {
+    Crossed { bid: f64, ask: f64 },
+    TimestampRegression { previous: u64 },
+    Silence { micros: u64 },
+    // A snapshot in the middle of the stream: the feed reconnected and updates may be missing
+    Resnapshot,
+    // Removal of a level inside the held price range that the book does not hold and did not
+    // uncross itself: an update was missed
+    UnknownLevel { price: f64 },
+}
+
+#[derive(Debug, Clone)]
+pub struct BookIssue {
+    pub timestamp: u64,
+    pub kind: BookIssueKind,
+}
+
+impl BookIssueKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BookIssueKind::Crossed { .. } => "crossed book",
+            BookIssueKind::TimestampRegression { .. } => "timestamp regression",
+            BookIssueKind::Silence { .. } => "silence",
+            BookIssueKind::Resnapshot => "resnapshot",
+            BookIssueKind::UnknownLevel { .. } => "unknown level removed",
+        }
+    }
+}
+
+// Levels keyed by the bit pattern of the price, which orders like the price for positive floats
+#[derive(Debug, Clone, Default)]
+pub struct OrderBook {
+    pub exchange: String,
+    pub symbol: String,
+    pub timestamp: u64,
+    pub local_timestamp: u64,
+    bids: BTreeMap<u64, f64>,
+    asks: BTreeMap<u64, f64>,
+    // Levels dropped by uncrossing that the exchange has not removed explicitly yet
+    uncrossed_bids: BTreeSet<u64>,
+    uncrossed_asks: BTreeSet<u64>,
+}
+
+impl OrderBook {
+    pub fn new(exchange: &str, symbol: &str) -> Self {
+        OrderBook { exchange: exchange.to_string(), symbol: symbol.to_string(), ..Default::default() }
+    }
+
+    pub fn clear(&mut self) {
+        self.bids.clear();
+        self.asks.clear();
+        self.uncrossed_bids.clear();
+        self.uncrossed_asks.clear();
+    }
+
+    // Sets the level; returns false when a removal hits a level that is not in the book.
+    // Removals of levels the book uncrossed itself, or beyond the deepest held level (outside
+    // the snapshot depth), are expected and return true.
+    pub fn apply(&mut self, update: &L2Update, semantics: &BookSemantics) -> bool {
+        self.timestamp = update.timestamp;
+        self.local_timestamp = update.local_timestamp;
+        let key = update.price.to_bits();
+        let is_bid = update.side == "bid" || update.side == "buy";
+        let (side, other, uncrossed, other_uncrossed) = if is_bid {
+            (&mut self.bids, &mut self.asks, &mut self.uncrossed_bids, &mut self.uncrossed_asks)
+        } else {
+            (&mut self.asks, &mut self.bids, &mut self.uncrossed_asks, &mut self.uncrossed_bids)
+        };
+        if update.amount <= 0.0 {
+            if side.remove(&key).is_some() || uncrossed.remove(&key) {
+                return true;
+            }
+            let beyond_depth = if is_bid {
+                side.keys().next().is_none_or(|deepest| key < *deepest)
+            } else {
+                side.keys().next_back().is_none_or(|deepest| key > *deepest)
+            };
+            return beyond_depth;
+        }
+        side.insert(key, update.amount);
+        uncrossed.remove(&key);
+        if semantics.uncross && !update.is_snapshot {
+            let crossed: Vec<u64> = if is_bid {
+                other.range(..=key).map(|(k, _)| *k).collect()
+            } else {
+                other.range(key..).map(|(k, _)| *k).collect()
+            };
+            for k in crossed {
+                other.remove(&k);
+                other_uncrossed.insert(k);
+            }
+        }
+        true
+    }
+
+    pub fn best_bid(&self) -> Option<Level> {
+        self.bids.iter().next_back().map(|(k, a)| Level { price: f64::from_bits(*k), amount: *a })
+    }
+
+    pub fn best_ask(&self) -> Option<Level> {
+        self.asks.iter().next().map(|(k, a)| Level { price: f64::from_bits(*k), amount: *a })
+    }
+
+    pub fn mid(&self) -> Option<f64> {
+        Some(0.5 * (self.best_bid()?.price + self.best_ask()?.price))
+    }
+
+    pub fn is_crossed(&self) -> bool {
+        matches!((self.best_bid(), self.best_ask()), (Some(b), Some(a)) if b.price >= a.price)
+    }
+
+    // Best `levels` levels per side, best first
+    pub fn bids(&self, levels: usize) -> Vec<Level> {
+        self.bids.iter().rev().take(levels).map(|(k, a)| Level { price: f64::from_bits(*k), amount: *a }).collect()
+    }
+
+    pub fn asks(&self, levels: usize) -> Vec<Level> {
+        self.asks.iter().take(levels).map(|(k, a)| Level { price: f64::from_bits(*k), amount: *a }).collect()
+    }
+
+    // Amount resting within `distance` (relative to the mid) on each side
+    pub fn depth_within(&self, distance: f64) -> (f64, f64) {
+        let Some(mid) = self.mid() else { return (0.0, 0.0) };
+        let bid = self.bids.iter().rev()
+            .take_while(|(k, _)| f64::from_bits(**k) >= mid * (1.0 - distance))
+            .map(|(_, a)| a)
+            .sum();
+        let ask = self.asks.iter()
+            .take_while(|(k, _)| f64::from_bits(**k) <= mid * (1.0 + distance))
+            .map(|(_, a)| a)
+            .sum();
+        (bid, ask)
+    }
+
+    pub fn snapshot(&self, levels: usize) -> BookSnapshot {
+        BookSnapshot {
+            exchange: self.exchange.clone(),
+            symbol: self.symbol.clone(),
+            timestamp: self.timestamp,
+            local_timestamp: self.local_timestamp,
+            bids: self.bids(levels),
+            asks: self.asks(levels),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplayResult {
+    pub book: OrderBook, // state after the last update
+    pub snapshots: Vec<BookSnapshot>,
+    pub issues: Vec<BookIssue>,
+}
+
+// Replays updates (one exchange / symbol) and emits a snapshot of `levels` levels every
+// `interval` microseconds, taken as of the last update at or before each grid time.
+// An interval of 0 emits a snapshot after every batch instead.
+// Updates sharing a timestamp are applied as one batch before the book is checked, since
+// exchanges publish a batch that is only consistent as a whole.
+pub fn replay(updates: &[L2Update], interval: u64, levels: usize) -> ReplayResult {
+    let (exchange, symbol) = updates.first().map_or(("", ""), |u| (u.exchange.as_str(), u.symbol.as_str()));
+    let semantics = BookSemantics::for_exchange(exchange);
+    let mut book = OrderBook::new(exchange, symbol);
+    let mut snapshots = Vec::new();
+    let mut issues = Vec::new();
+    let mut next_snapshot: Option<u64> = None;
+    let mut in_snapshot = false;
+    let mut seen_delta = false;
+
+    let mut start = 0;
+    while start < updates.len() {
+        let timestamp = updates[start].timestamp;
+        let end = start + updates[start..].iter().take_while(|u| u.timestamp == timestamp).count();
+
+        // Emit grid snapshots from the state before this batch
+        if let Some(next) = next_snapshot.as_mut() {
+            while *next < timestamp {
+                let mut snapshot = book.snapshot(levels);
+                snapshot.timestamp = *next;
+                snapshots.push(snapshot);
+                *next += interval;
+            }
+        }
+
+        if book.timestamp > timestamp {
+            issues.push(BookIssue { timestamp, kind: BookIssueKind::TimestampRegression { previous: book.timestamp } });
+        } else if seen_delta && timestamp - book.timestamp > semantics.max_silence {
+            issues.push(BookIssue { timestamp, kind: BookIssueKind::Silence { micros: timestamp - book.timestamp } });
+        }
+
+        for update in &updates[start..end] {
+            if update.is_snapshot {
+                // A new snapshot replaces the book
+                if !in_snapshot {
+                    if seen_delta {
+                        issues.push(BookIssue { timestamp, kind: BookIssueKind::Resnapshot });
+                    }
+                    book.clear();
+                    in_snapshot = true;
+                }
+            } else {
+                in_snapshot = false;
+                seen_delta = true;
+            }
+            if !book.apply(update, &semantics) {
+                issues.push(BookIssue { timestamp, kind: BookIssueKind::UnknownLevel { price: update.price } });
+            }
+        }
+
+        if !in_snapshot && book.is_crossed() {
+            let (bid, ask) = (book.best_bid().unwrap().price, book.best_ask().unwrap().price);
+            issues.push(BookIssue { timestamp, kind: BookIssueKind::Crossed { bid, ask } });
+        }
+        if interval == 0 && !book.bids.is_empty() && !book.asks.is_empty() {
+            snapshots.push(book.snapshot(levels));
+        }
+        if next_snapshot.is_none() && interval > 0 {
+            next_snapshot = Some((timestamp / interval + 1) * interval);
+        }
+        start = end;
+    }
+
+    ReplayResult { book, snapshots, issues }
+}
@@ -0,0 +1,282 @@
+// Error function (Numerical Recipes erfc Chebyshev fit, fractional error < 1.2e-7)
+pub fn erf(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.26551223
+        + t * (1.00002368
+        + t * (0.37409196
+        + t * (0.09678418
+        + t * (-0.18628806
+        + t * (0.27886807
+        + t * (-1.13520398
+        + t * (1.48851587
+        + t * (-0.82215223
+        + t * 0.17087277))))))));
+    let erfc = t * poly.exp();
+    if x >= 0.0 { 1.0 - erfc } else { erfc - 1.0 }
+}
+
+// Standard normal CDF
+pub fn norm_cdf(x: f64) -> f64 {
+    0.5 * (1.0 + erf(x / std::f64::consts::SQRT_2))
+}
+
+// Inverse standard normal CDF (Acklam's rational approximation, relative error < 1.15e-9)
+pub fn norm_ppf(p: f64) -> f64 {
+    if p <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if p >= 1.0 {
+        return f64::INFINITY;
+    }
+
+    let a = [-3.969683028665376e+01, 2.209460984245205e+02, -2.759285104469687e+02,
+             1.38357751867269e+02, -3.066479806614716e+01, 2.506628277459239e+00];
+    let b = [-5.447609879822406e+01, 1.615858368580409e+02, -1.556989798598866e+02,
+             6.680131188771972e+01, -1.328068155288572e+01];
+    let c = [-7.784894002430293e-03, -3.223964580411365e-01, -2.400758277161838e+00,
+             -2.549732539343734e+00, 4.374664141464968e+00, 2.938163982698783e+00];
+    let d = [7.784695709041462e-03, 3.224671290700398e-01, 2.445134137142996e+00,
+             3.754408661907416e+00];
+
+    let p_low = 0.02425;
+    if p < p_low {
+        let q = (-2.0 * p.ln()).sqrt();
+        (((((c[0] * q + c[1]) * q + c[2]) * q + c[3]) * q + c[4]) * q + c[5])
+            / ((((d[0] * q + d[1]) * q + d[2]) * q + d[3]) * q + 1.0)
+    } else if p <= 1.0 - p_low {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((a[0] * r + a[1]) * r + a[2]) * r + a[3]) * r + a[4]) * r + a[5]) * q
+            / (((((b[0] * r + b[1]) * r + b[2]) * r + b[3]) * r + b[4]) * r + 1.0)
+    } else {
+        let q = (-2.0 * (1.0 - p).ln()).sqrt();
+        -(((((c[0] * q + c[1]) * q + c[2]) * q + c[3]) * q + c[4]) * q + c[5])
+            / ((((d[0] * q + d[1]) * q + d[2]) * q + d[3]) * q + 1.0)
+    }
+}
+
+fn mean_std(data: &[f64]) -> (f64, f64) {
+    let n = data.len() as f64;
+    let mean = data.iter().sum::<f64>() / n;
+    let variance = data.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, variance.sqrt())
+}
+
+// Sample autocorrelation at the given lag
+pub fn autocorrelation(data: &[f64], lag: usize) -> f64 {
+    let n = data.len();
+    if lag >= n || n < 2 {
+        return 0.0;
+    }
+    let mean = data.iter().sum::<f64>() / n as f64;
+    let denom: f64 = data.iter().map(|x| (x - mean).powi(2)).sum();
+    if denom == 0.0 {
+        return 0.0;
+    }
+    let num: f64 = (lag..n).map(|t| (data[t] - mean) * (data[t - lag] - mean)).sum();
+    num / denom
+}
+
+// Jarque-Bera statistic n/6 (S^2 + K^2/4) with population moments; p-value from chi-squared(2)
+pub fn jarque_bera(data: &[f64]) -> (f64, f64) {
+    let n = data.len() as f64;
+    if n < 3.0 {
+        return (0.0, 1.0);
+    }
+    let mean = data.iter().sum::<f64>() / n;
+    let m2 = data.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+    if m2 == 0.0 {
+        return (0.0, 1.0);
+    }
+    let m3 = data.iter().map(|x| (x - mean).powi(3)).sum::<f64>() / n;
+    let m4 = data.iter().map(|x| (x - mean).powi(4)).sum::<f64>() / n;
+    let skewness = m3 / m2.powf(1.5);
+    let excess_kurtosis = m4 / (m2 * m2) - 3.0;
+    let stat = n / 6.0 * (skewness.powi(2) + excess_kurtosis.powi(2) / 4.0);
+    (stat, (-stat / 2.0).exp())
+}
+
+// Kolmogorov-Smirnov distance to a normal with the sample mean and std.
+// The p-value uses the asymptotic Kolmogorov distribution, which is conservative
+// when the parameters are estimated from the same sample.
+pub fn ks_normal(data: &[f64]) -> (f64, f64) {
+    let n = data.len();
+    if n < 2 {
+        return (0.0, 1.0);
+    }
+    let (mean, std) = mean_std(data);
+    if std == 0.0 {
+        return (0.0, 1.0);
+    }
+    let mut z: Vec<f64> = data.iter().map(|x| (x - mean) / std).collect();
+    z.sort_by(|a, b| a.total_cmp(b));
+
+    let nf = n as f64;
+    let stat = z.iter().enumerate().map(|(i, &v)| {
+        let cdf = norm_cdf(v);
+        (cdf - i as f64 / nf).max((i + 1) as f64 / nf - cdf)
+    }).fold(0.0, f64::max);
+
+    let lambda = (nf.sqrt() + 0.12 + 0.11 / nf.sqrt()) * stat;
+    let p_value = if lambda < 0.2 {
+        1.0
+    } else {
+        (1..=100).map(|k| {
+            let sign = if k % 2 == 1 { 2.0 } else { -2.0 };
+            sign * (-2.0 * (k * k) as f64 * lambda * lambda).exp()
+        }).sum::<f64>().clamp(0.0, 1.0)
+    };
+    (stat, p_value)
+}
+
+// Anderson-Darling test of normality with estimated mean and std.
+// Returns the small-sample adjusted A*^2 and its p-value (D'Agostino & Stephens, 1986).
+pub fn anderson_darling_normal(data: &[f64]) -> (f64, f64) {
+    let n = data.len();
+    if n < 3 {
+        return (0.0, 1.0);
+    }
+    let (mean, std) = mean_std(data);
+    if std == 0.0 {
+        return (0.0, 1.0);
+    }
+    let mut z: Vec<f64> = data.iter().map(|x| (x - mean) / std).collect();
+    z.sort_by(|a, b| a.total_cmp(b));
+
+    let nf = n as f64;
+    let sum: f64 = (0..n).map(|i| {
+        let lower = norm_cdf(z[i]).max(1e-300).ln();
+        let upper = norm_cdf(-z[n - 1 - i]).max(1e-300).ln();
+        (2 * i + 1) as f64 * (lower + upper)
+    }).sum();
+    let a2 = -nf - sum / nf;
+    let a = a2 * (1.0 + 0.75 / nf + 2.25 / (nf * nf));
+
+    let p_value = if a >= 0.6 {
+        (1.2937 - 5.709 * a + 0.0186 * a * a).exp()
+    } else if a >= 0.34 {
+        (0.9177 - 4.279 * a - 1.38 * a * a).exp()
+    } else if a >= 0.2 {
+        1.0 - (-8.318 + 42.796 * a - 59.938 * a * a).exp()
+    } else {
+        1.0 - (-13.436 + 101.14 * a - 223.73 * a * a).exp()
+    };
+    (a, p_value.clamp(0.0, 1.0))
+}
+
+// Partial autocorrelations for lags 1..=max_lag (Durbin-Levinson recursion)
+pub fn pacf(data: &[f64], max_lag: usize) -> Vec<f64> {
+    let rho: Vec<f64> = (0..=max_lag).map(|k| autocorrelation(data, k)).collect();
+    let mut result = Vec::with_capacity(max_lag);
+    let mut phi_prev: Vec<f64> = Vec::new();
+    for k in 1..=max_lag {
+        let num = rho[k] - (1..k).map(|j| phi_prev[j - 1] * rho[k - j]).sum::<f64>();
+        let den = 1.0 - (1..k).map(|j| phi_prev[j - 1] * rho[j]).sum::<f64>();
+        let phi_kk = if den.abs() > 1e-12 { num / den } else { 0.0 };
+        let mut phi: Vec<f64> = (1..k).map(|j| phi_prev[j - 1] - phi_kk * phi_prev[k - j - 1]).collect();
+        phi.push(phi_kk);
+        result.push(phi_kk);
+        phi_prev = phi;
+    }
+    result
+}
+
+// ln Gamma(x) for x > 0 (Lanczos approximation, g = 7)
+pub fn ln_gamma(x: f64) -> f64 {
+    const COEFFS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let series = COEFFS[1..].iter().enumerate()
+        .fold(COEFFS[0], |acc, (i, c)| acc + c / (x + (i + 1) as f64));
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + series.ln()
+}
+
+// Regularized incomplete beta I_x(a, b) (Numerical Recipes continued fraction)
+pub fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    let tiny = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < tiny {
+        d = tiny;
+    }
+    d = 1.0 / d;
+    let mut h = d;
+    for m in 1..300 {
+        let m = m as f64;
+        let m2 = 2.0 * m;
+        for aa in [
+            m * (b - m) * x / ((a + m2 - 1.0) * (a + m2)),
+            -(a + m) * (a + b + m) * x / ((a + m2) * (a + m2 + 1.0)),
+        ] {
+            d = 1.0 + aa * d;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = 1.0 + aa / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            h *= d * c;
+        }
+        if (d * c - 1.0).abs() < 1e-14 {
+            break;
+        }
+    }
+    h
+}
+
+// Student-t CDF with `df` degrees of freedom
+pub fn student_t_cdf(t: f64, df: f64) -> f64 {
+    let tail = 0.5 * incomplete_beta(df / 2.0, 0.5, df / (df + t * t));
+    if t >= 0.0 { 1.0 - tail } else { tail }
+}
+
+// Inverse Student-t CDF by bisection
+pub fn student_t_ppf(p: f64, df: f64) -> f64 {
+    if p <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if p >= 1.0 {
+        return f64::INFINITY;
+    }
+    let (mut lo, mut hi) = (-1e3, 1e3);
+    for _ in 0..200 {
+        let mid = 0.5 * (lo + hi);
+        if student_t_cdf(mid, df) < p { lo = mid } else { hi = mid }
+        if hi - lo < 1e-12 {
+            break;
+        }
+    }
+    0.5 * (lo + hi)
+}
@@ -0,0 +1,246 @@
+use crate::config;
+use crate::base::{Bar, read_trades};
+use crate::base::stats::norm_cdf;
+use crate::ch2::time_bar::compute_time_bars;
+use crate::ch2::cusum_filter::compute_cusum_event_indices;
+use crate::ch3::volatility::{Estimator, volatility};
+use std::error::Error;
+use chrono::{DateTime, Utc};
+use plotters::prelude::*;
+
+// A prediction attached to an event, active from t0 until t1 (e.g. vertical barrier)
+#[derive(Debug, Clone)]
+pub struct BetEvent {
+    pub t0: DateTime<Utc>,
+    pub t1: DateTime<Utc>,
+    pub prob: f64, // predicted probability of the predicted class
+    pub side: f64, // predicted label (-1, 0, 1)
+}
+
+// Order produced by the dynamic (sigmoid) sizing
+#[derive(Debug, Clone)]
+pub struct DynamicOrder {
+    pub time: DateTime<Utc>,
+    pub target_pos: i64,
+    pub limit_price: Option<f64>, // None when no trade is needed
+}
+
+// Bet size from predicted probability with K classes
+// z = (p - 1/K) / sqrt(p(1-p)),  m = side * (2 * Phi(z) - 1)
+pub fn get_signal(prob: f64, side: f64, num_classes: usize) -> f64 {
+    let p = prob.clamp(1e-9, 1.0 - 1e-9);
+    let z = (p - 1.0 / num_classes as f64) / (p * (1.0 - p)).sqrt();
+    side * (2.0 * norm_cdf(z) - 1.0)
+}
+
+// Average of the signals still active at each bar (t0 <= t < t1)
+pub fn avg_active_signals(bars: &[Bar], events: &[BetEvent], signals: &[f64]) -> Vec<f64> {
+    bars.iter().map(|bar| {
+        let mut sum = 0.0;
+        let mut count = 0;
+        for (event, signal) in events.iter().zip(signals) {
+            if event.t0 <= bar.time && bar.time < event.t1 {
+                sum += signal;
+                count += 1;
+            }
+        }
+        if count > 0 { sum / count as f64 } else { 0.0 }
+    }).collect()
+}
+
+// Round a signal to a multiple of step_size to avoid overtrading, capped at [-1, 1]
+pub fn discrete_signal(signal: f64, step_size: f64) -> f64 {
+    if step_size <= 0.0 {
+        return signal.clamp(-1.0, 1.0);
+    }
+    ((signal / step_size).round() * step_size).clamp(-1.0, 1.0)
+}
+
+// Per-bar target position in [-1, 1] from event probabilities
+pub fn compute_bet_sizes(bars: &[Bar], events: &[BetEvent], num_classes: usize, step_size: f64) -> Vec<f64> {
+    let signals: Vec<f64> = events.iter()
+        .map(|e| get_signal(e.prob, e.side, num_classes))
+        .collect();
+
+    avg_active_signals(bars, events, &signals)
+        .into_iter()
+        .map(|s| discrete_signal(s, step_size))
+        .collect()
+}
+
+// Sigmoid bet size for a price divergence x = f - p
+// m = x / sqrt(w + x^2)
+pub fn bet_size(w: f64, x: f64) -> f64 {
+    x / (w + x * x).sqrt()
+}
+
+// Calibrate w so that divergence x maps to bet size m
+pub fn get_w(x: f64, m: f64) -> f64 {
+    x * x * (1.0 / (m * m) - 1.0)
+}
+
+pub fn get_target_pos(w: f64, forecast: f64, price: f64, max_pos: i64) -> i64 {
+    (bet_size(w, forecast - price) * max_pos as f64).trunc() as i64
+}
+
+// Price at which the sigmoid gives bet size m
+pub fn inv_price(forecast: f64, w: f64, m: f64) -> f64 {
+    forecast - m * (w / (1.0 - m * m)).sqrt()
+}
+
+// Breakeven limit price for moving from pos to target_pos one unit at a time
+pub fn limit_price(target_pos: i64, pos: i64, forecast: f64, w: f64, max_pos: i64) -> Option<f64> {
+    if target_pos == pos {
+        return None;
+    }
+    let sgn = if target_pos > pos { 1 } else { -1 };
+    let mut sum = 0.0;
+    let mut j = pos + sgn;
+    loop {
+        sum += inv_price(forecast, w, j as f64 / max_pos as f64);
+        if j == target_pos {
+            break;
+        }
+        j += sgn;
+    }
+    Some(sum / (target_pos - pos).abs() as f64)
+}
+
+// Walk the bars with a forecast price per bar, assuming every order is filled
+pub fn compute_dynamic_orders(bars: &[Bar], forecasts: &[f64], w: f64, max_pos: i64) -> Vec<DynamicOrder> {
+    let mut orders = Vec::with_capacity(bars.len());
+    let mut pos = 0;
+
+    for (bar, &forecast) in bars.iter().zip(forecasts) {
+        let target_pos = get_target_pos(w, forecast, bar.close, max_pos);
+        let limit = limit_price(target_pos, pos, forecast, w, max_pos);
+        orders.push(DynamicOrder {
+            time: bar.time,
+            target_pos,
+            limit_price: limit,
+        });
+        pos = target_pos;
+    }
+    orders
+}
+
+// Standard deviation of close-to-close log returns
+pub fn return_std(bars: &[Bar]) -> f64 {
+    volatility(bars, Estimator::CloseToClose)
+}
+
+// Bet events on CUSUM triggers, held for hold_bars bars.
+// Side follows the triggering move; until a classifier is trained the probability
+// is a placeholder that grows with the size of that move relative to h.
+pub fn build_cusum_bet_events(bars: &[Bar], h: f64, hold_bars: usize) -> Vec<BetEvent> {
+    compute_cusum_event_indices(bars, h).into_iter().filter(|&i| i > 0).map(|i| {
+        let r = (bars[i].close / bars[i - 1].close).ln();
+        let t1 = bars[(i + hold_bars).min(bars.len() - 1)].time;
+        BetEvent {
+            t0: bars[i].time,
+            t1,
+            prob: norm_cdf(r.abs() / h),
+            side: r.signum(),
+        }
+    }).collect()
+}
+
+pub fn run_bet_sizing() -> Result<(), Box<dyn Error>> {
+    let file_path = config::TARDIS_CSV_PATH;
+    println!("Reading trades from {}...", file_path);
+    let trades = read_trades(file_path)?;
+    println!("Read {} trades.", trades.len());
+
+    // 1. Compute Time Bars (15 minutes)
+    let time_interval_minutes = 15;
+    println!("Computing {} minute time bars...", time_interval_minutes);
+    let bars = compute_time_bars(&trades, time_interval_minutes);
+    println!("Generated {} time bars.", bars.len());
+
+    // 2. CUSUM events with h = 1 std dev of returns
+    let h = return_std(&bars);
+    let hold_bars = 4; // vertical barrier: 1 hour
+    let events = build_cusum_bet_events(&bars, h, hold_bars);
+    println!("Detected {} CUSUM events (h = {:.6}).", events.len(), h);
+
+    // 3. Static sizing
+    let step_size = 0.05;
+    let sizes = compute_bet_sizes(&bars, &events, 2, step_size);
+    let active = sizes.iter().filter(|s| **s != 0.0).count();
+    let avg_abs = sizes.iter().map(|s| s.abs()).sum::<f64>() / sizes.len() as f64;
+    println!("Bars with an open bet: {} / {}, mean |size| = {:.4}", active, sizes.len(), avg_abs);
+
+    // 4. Dynamic sizing: forecast moves the price by size * h
+    let max_pos = 100;
+    let w = get_w(10.0, 0.95); // a $10 divergence gives a 95% bet
+    let forecasts: Vec<f64> = bars.iter().zip(&sizes).map(|(b, s)| b.close * (1.0 + s * h)).collect();
+    let orders = compute_dynamic_orders(&bars, &forecasts, w, max_pos);
+    let num_orders = orders.iter().filter(|o| o.limit_price.is_some()).count();
+    println!("Dynamic sizing (w = {:.2}, max_pos = {}): {} orders", w, max_pos, num_orders);
+    if let Some(order) = orders.iter().rev().find(|o| o.limit_price.is_some()) {
+        println!("Last order at {}: target {} @ limit {:.2}", order.time, order.target_pos, order.limit_price.unwrap());
+    }
+
+    // 5. Draw Chart
+    std::fs::create_dir_all("src/ch10/result")?;
+    let output_path = "src/ch10/result/bet_sizing.png";
+    println!("Drawing chart to {}...", output_path);
+    draw_bet_size_chart(&bars, &sizes, output_path)?;
+    println!("Chart saved to {}", output_path);
+
+    // Open file
+    #[cfg(target_os = "windows")]
+    std::process::Command::new("cmd")
+        .args(["/C", "start", output_path])
+        .spawn()?;
+
+    #[cfg(target_os = "macos")]
+    std::process::Command::new("open")
+        .arg(output_path)
+        .spawn()?;
+
+    #[cfg(target_os = "linux")]
+    std::process::Command::new("xdg-open")
+        .arg(output_path)
+        .spawn()?;
+
+    Ok(())
+}
+
+fn draw_bet_size_chart(bars: &[Bar], sizes: &[f64], output_path: &str) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(output_path, (1280, 900)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let (upper, lower) = root.split_vertically(600);
+
+    let min_time = bars.first().unwrap().time;
+    let max_time = bars.last().unwrap().time;
+
+    let min_price = bars.iter().map(|b| b.low).fold(f64::INFINITY, f64::min);
+    let max_price = bars.iter().map(|b| b.high).fold(f64::NEG_INFINITY, f64::max);
+
+    let mut price_chart = ChartBuilder::on(&upper)
+        .caption("Bet Sizing on 15m Time Bars", ("sans-serif", 30).into_font())
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(min_time..max_time, min_price..max_price)?;
+
+    price_chart.configure_mesh().draw()?;
+    price_chart.draw_series(LineSeries::new(bars.iter().map(|b| (b.time, b.close)), &BLACK))?;
+
+    let mut size_chart = ChartBuilder::on(&lower)
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(min_time..max_time, -1.1..1.1)?;
+
+    size_chart.configure_mesh().y_desc("Target Position").draw()?;
+    size_chart.draw_series(
+        bars.iter().zip(sizes).map(|(b, &s)| {
+            let color = if s >= 0.0 { RGBColor(98, 209, 61) } else { RGBColor(209, 61, 61) };
+            Rectangle::new([(b.time, 0.0), (b.time + chrono::Duration::minutes(15), s)], color.filled())
+        })
+    )?;
+
+    Ok(())
+}
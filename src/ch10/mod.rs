@@ -0,0 +1,3 @@
+pub mod bet_sizing;
+
+pub use bet_sizing::run_bet_sizing;
@@ -0,0 +1,299 @@
+use crate::config;
+use crate::base::{Bar, read_trades};
+use crate::ch2::cusum_filter::compute_cusum_event_indices;
+use crate::ch10::bet_sizing::return_std;
+use crate::ch14::backtest_statistics::sharpe_ratio;
+use crate::ch2::calibration::{BarKind, calibrate};
+use crate::ch2::time_bar::compute_time_bars;
+use std::error::Error;
+use chrono::{DateTime, Duration, Utc};
+use plotters::prelude::*;
+use rayon::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct CscvResult {
+    pub logits: Vec<f64>,   // one per IS/OOS combination
+    pub is_sharpe: Vec<f64>,  // IS Sharpe of the IS-optimal strategy
+    pub oos_sharpe: Vec<f64>, // OOS Sharpe of the same strategy
+    pub pbo: f64,             // P[logit <= 0]
+    pub degradation_slope: f64,     // OOS = intercept + slope * IS
+    pub degradation_intercept: f64,
+    pub prob_loss: f64,       // P[OOS Sharpe < 0]
+}
+
+// All k-element subsets of 0..n in lexicographic order
+pub fn combinations(n: usize, k: usize) -> Vec<Vec<usize>> {
+    let mut result = Vec::new();
+    let mut current: Vec<usize> = (0..k).collect();
+    if k > n {
+        return result;
+    }
+    loop {
+        result.push(current.clone());
+        let Some(i) = (0..k).rev().find(|&i| current[i] < n - k + i) else {
+            return result;
+        };
+        current[i] += 1;
+        for j in i + 1..k {
+            current[j] = current[j - 1] + 1;
+        }
+    }
+}
+
+// Bar returns of an always-in CUSUM strategy (side of the last event's return),
+// summed into fixed time buckets so every bar type shares the same rows
+pub fn cusum_strategy_returns(bars: &[Bar], h: f64, start: DateTime<Utc>, bucket_secs: i64, num_buckets: usize) -> Vec<f64> {
+    let events = compute_cusum_event_indices(bars, h);
+    let mut returns = vec![0.0; num_buckets];
+    let mut next_event = 0;
+    let mut side = 0.0;
+
+    for i in 1..bars.len() {
+        let r = (bars[i].close / bars[i - 1].close).ln();
+        let bucket = ((bars[i].time - start).num_seconds() / bucket_secs).max(0) as usize;
+        if bucket < num_buckets {
+            returns[bucket] += side * r;
+        }
+        if next_event < events.len() && events[next_event] == i {
+            side = r.signum();
+            next_event += 1;
+        }
+    }
+    returns
+}
+
+// Combinatorially symmetric cross-validation.
+// matrix[t][n] is the return of strategy n in period t. Rows are split into num_blocks
+// contiguous blocks; every half of the blocks is used once as IS, its complement as OOS.
+pub fn cscv(matrix: &[Vec<f64>], num_blocks: usize) -> Result<CscvResult, Box<dyn Error>> {
+    let num_rows = matrix.len();
+    let num_strategies = matrix.first().map(|r| r.len()).unwrap_or(0);
+    if num_blocks == 0 || !num_blocks.is_multiple_of(2) {
+        return Err(format!("CSCV needs an even, positive number of blocks, got {}", num_blocks).into());
+    }
+    if num_strategies == 0 || matrix.iter().any(|r| r.len() != num_strategies) {
+        return Err("CSCV needs a non-empty matrix with the same number of strategies in every row".into());
+    }
+    if num_rows < num_blocks {
+        return Err(format!("CSCV needs at least {} rows, got {}", num_blocks, num_rows).into());
+    }
+    let block_size = num_rows / num_blocks;
+    let blocks: Vec<Vec<usize>> = (0..num_blocks).map(|b| (b * block_size..(b + 1) * block_size).collect()).collect();
+
+    let column_sharpes = |rows: &[usize]| -> Vec<f64> {
+        (0..num_strategies).map(|n| {
+            let column: Vec<f64> = rows.iter().map(|&t| matrix[t][n]).collect();
+            sharpe_ratio(&column)
+        }).collect()
+    };
+
+    let trials: Vec<(f64, f64, f64)> = combinations(num_blocks, num_blocks / 2).into_par_iter().map(|is_blocks| {
+        let is_rows: Vec<usize> = is_blocks.iter().flat_map(|&b| blocks[b].iter().copied()).collect();
+        let oos_rows: Vec<usize> = (0..num_blocks)
+            .filter(|b| !is_blocks.contains(b))
+            .flat_map(|b| blocks[b].iter().copied())
+            .collect();
+
+        let is_sr = column_sharpes(&is_rows);
+        let oos_sr = column_sharpes(&oos_rows);
+
+        let best = (0..num_strategies).max_by(|&a, &b| is_sr[a].total_cmp(&is_sr[b])).unwrap();
+        // Relative rank of the IS winner among OOS performances, in (0, 1)
+        let rank = oos_sr.iter().filter(|&&sr| sr <= oos_sr[best]).count() as f64;
+        let omega = rank / (num_strategies as f64 + 1.0);
+
+        ((omega / (1.0 - omega)).ln(), is_sr[best], oos_sr[best])
+    }).collect();
+
+    let logits: Vec<f64> = trials.iter().map(|t| t.0).collect();
+    let is_sharpe: Vec<f64> = trials.iter().map(|t| t.1).collect();
+    let oos_sharpe: Vec<f64> = trials.iter().map(|t| t.2).collect();
+
+    let num_trials = logits.len() as f64;
+    let pbo = logits.iter().filter(|&&l| l <= 0.0).count() as f64 / num_trials;
+    let prob_loss = oos_sharpe.iter().filter(|&&sr| sr < 0.0).count() as f64 / num_trials;
+
+    let mean_is = is_sharpe.iter().sum::<f64>() / num_trials;
+    let mean_oos = oos_sharpe.iter().sum::<f64>() / num_trials;
+    let sxx: f64 = is_sharpe.iter().map(|x| (x - mean_is).powi(2)).sum();
+    let sxy: f64 = is_sharpe.iter().zip(&oos_sharpe).map(|(x, y)| (x - mean_is) * (y - mean_oos)).sum();
+    let degradation_slope = if sxx > 0.0 { sxy / sxx } else { 0.0 };
+    let degradation_intercept = mean_oos - degradation_slope * mean_is;
+
+    Ok(CscvResult { logits, is_sharpe, oos_sharpe, pbo, degradation_slope, degradation_intercept, prob_loss })
+}
+
+pub fn run_backtest_overfitting() -> Result<(), Box<dyn Error>> {
+    let file_path = config::TARDIS_CSV_PATH;
+    println!("Reading trades from {}...", file_path);
+    let trades = read_trades(file_path)?;
+    println!("Read {} trades.", trades.len());
+    if trades.len() < 2 {
+        return Err("Not enough trades".into());
+    }
+
+    // 1. Strategy sweep: bar type x CUSUM threshold, returns on a common 5 minute grid
+    let start = DateTime::from_timestamp_micros(trades[0].timestamp as i64).unwrap();
+    let end = DateTime::from_timestamp_micros(trades[trades.len() - 1].timestamp as i64).unwrap();
+    let bucket_secs = 5 * 60;
+    let num_buckets = ((end - start).num_seconds() / bucket_secs) as usize + 1;
+
+    // Every bar type sized to the number of 15 minute time bars. Imbalance bars can miss the
+    // target; types with too few bars, and thresholds that never trade, would only add flat
+    // columns that tie in the OOS ranking, so they are left out.
+    let num_blocks = 16;
+    let min_bars = num_blocks * 2;
+    let time_bars = compute_time_bars(&trades, 15);
+    let target = time_bars.len();
+    let mut series = vec![("time", time_bars)];
+    for kind in BarKind::ALL {
+        let calibration = calibrate(&trades, kind, target, Duration::minutes(15));
+        series.push((kind.name(), kind.build(&trades, calibration.threshold)));
+    }
+    let multipliers = [0.5, 1.0, 1.5, 2.0, 3.0];
+
+    let mut columns = Vec::new();
+    for (bar_type, bars) in &series {
+        if bars.len() < min_bars {
+            println!("{:<17} {:>5} bars, left out (fewer than {})", bar_type, bars.len(), min_bars);
+            continue;
+        }
+        let std = return_std(bars);
+        let mut kept = 0;
+        for m in multipliers {
+            let column = cusum_strategy_returns(bars, m * std, start, bucket_secs, num_buckets);
+            if column.iter().any(|r| *r != 0.0) {
+                columns.push(column);
+                kept += 1;
+            }
+        }
+        println!("{:<17} {:>5} bars, {} of {} thresholds trade", bar_type, bars.len(), kept, multipliers.len());
+    }
+    let matrix: Vec<Vec<f64>> = (0..num_buckets)
+        .map(|t| columns.iter().map(|c| c[t]).collect())
+        .collect();
+
+    // 2. CSCV
+    if num_buckets < num_blocks * 2 {
+        return Err("Not enough periods for CSCV".into());
+    }
+    println!(
+        "Running CSCV on {} periods x {} strategies with {} blocks...",
+        num_buckets, columns.len(), num_blocks
+    );
+    let result = cscv(&matrix, num_blocks)?;
+
+    println!("--- Probability of Backtest Overfitting ---");
+    println!("Combinations: {}", result.logits.len());
+    println!("PBO: {:.4}", result.pbo);
+    println!(
+        "Performance degradation: OOS SR = {:.4} + {:.4} * IS SR",
+        result.degradation_intercept, result.degradation_slope
+    );
+    println!("Probability of loss: {:.4}", result.prob_loss);
+
+    // 3. Draw Charts
+    std::fs::create_dir_all("src/ch11/result")?;
+    let logit_path = "src/ch11/result/pbo_logits.png";
+    draw_logit_histogram(&result, logit_path)?;
+    println!("Chart saved to {}", logit_path);
+
+    let output_path = "src/ch11/result/pbo_is_vs_oos.png";
+    println!("Drawing chart to {}...", output_path);
+    draw_sharpe_scatter(&result, output_path)?;
+    println!("Chart saved to {}", output_path);
+
+    // Open file
+    #[cfg(target_os = "windows")]
+    std::process::Command::new("cmd")
+        .args(["/C", "start", output_path])
+        .spawn()?;
+
+    #[cfg(target_os = "macos")]
+    std::process::Command::new("open")
+        .arg(output_path)
+        .spawn()?;
+
+    #[cfg(target_os = "linux")]
+    std::process::Command::new("xdg-open")
+        .arg(output_path)
+        .spawn()?;
+
+    Ok(())
+}
+
+fn draw_logit_histogram(result: &CscvResult, output_path: &str) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(output_path, (1024, 768)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let min_l = result.logits.iter().copied().fold(f64::INFINITY, f64::min).min(-0.5);
+    let max_l = result.logits.iter().copied().fold(f64::NEG_INFINITY, f64::max).max(0.5);
+    let num_bins = 40;
+    let width = (max_l - min_l) / num_bins as f64;
+    let mut counts = vec![0usize; num_bins];
+    for l in &result.logits {
+        let bin = (((l - min_l) / width) as usize).min(num_bins - 1);
+        counts[bin] += 1;
+    }
+    let total = result.logits.len() as f64;
+    let max_freq = counts.iter().map(|&c| c as f64 / total).fold(0.0, f64::max);
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption(format!("Logit Distribution (PBO = {:.2})", result.pbo), ("sans-serif", 30).into_font())
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(min_l..max_l, 0.0..max_freq * 1.1)?;
+
+    chart.configure_mesh().x_desc("Logit").y_desc("Frequency").draw()?;
+
+    chart.draw_series(counts.iter().enumerate().map(|(i, &c)| {
+        let x0 = min_l + i as f64 * width;
+        let color = if x0 + width / 2.0 <= 0.0 { RED.mix(0.6) } else { BLUE.mix(0.6) };
+        Rectangle::new([(x0, 0.0), (x0 + width, c as f64 / total)], color.filled())
+    }))?;
+
+    chart.draw_series(LineSeries::new(vec![(0.0, 0.0), (0.0, max_freq * 1.1)], &BLACK))?;
+
+    Ok(())
+}
+
+fn draw_sharpe_scatter(result: &CscvResult, output_path: &str) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(output_path, (1024, 768)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let min_x = result.is_sharpe.iter().copied().fold(f64::INFINITY, f64::min);
+    let max_x = result.is_sharpe.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let min_y = result.oos_sharpe.iter().copied().fold(f64::INFINITY, f64::min);
+    let max_y = result.oos_sharpe.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let pad_x = ((max_x - min_x) * 0.05).max(1e-3);
+    let pad_y = ((max_y - min_y) * 0.05).max(1e-3);
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption(
+            format!(
+                "IS vs OOS Sharpe (slope = {:.2}, P[loss] = {:.2})",
+                result.degradation_slope, result.prob_loss
+            ),
+            ("sans-serif", 30).into_font(),
+        )
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d((min_x - pad_x)..(max_x + pad_x), (min_y - pad_y)..(max_y + pad_y))?;
+
+    chart.configure_mesh().x_desc("IS Sharpe").y_desc("OOS Sharpe").draw()?;
+
+    chart.draw_series(
+        result.is_sharpe.iter().zip(&result.oos_sharpe)
+            .map(|(&x, &y)| Circle::new((x, y), 2, BLUE.mix(0.3).filled()))
+    )?;
+
+    let fit = |x: f64| result.degradation_intercept + result.degradation_slope * x;
+    chart.draw_series(LineSeries::new(
+        vec![(min_x - pad_x, fit(min_x - pad_x)), (max_x + pad_x, fit(max_x + pad_x))],
+        &RED,
+    ))?;
+
+    Ok(())
+}
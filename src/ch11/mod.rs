@@ -0,0 +1,3 @@
+pub mod backtest_overfitting;
+
+pub use backtest_overfitting::run_backtest_overfitting;
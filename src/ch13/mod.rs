@@ -0,0 +1,3 @@
+pub mod synthetic_data;
+
+pub use synthetic_data::run_synthetic_data;
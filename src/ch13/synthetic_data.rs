@@ -0,0 +1,268 @@
+use crate::base::{Trade, draw_chart_file};
+use crate::ch2::tick_bar::compute_tick_bars;
+use crate::ch2::bar_builder::dollar_metric;
+use crate::ch2::dollar_bar::compute_dollar_bars;
+use crate::ch2::cusum_filter::compute_cusum_events;
+use crate::ch10::bet_sizing::return_std;
+use std::error::Error;
+use plotters::prelude::*;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand_distr::{Distribution, Exp, Normal};
+
+// Discrete Ornstein-Uhlenbeck process
+// P_t = (1 - phi) * E0 + phi * P_{t-1} + sigma * eps_t,  phi = 2^(-1 / half_life)
+#[derive(Debug, Clone)]
+pub struct OuParams {
+    pub forecast: f64,      // long-run mean E0
+    pub half_life: f64,     // in steps (trades for the generator, bars for the fit)
+    pub sigma: f64,         // per-step noise
+    pub initial_price: f64,
+}
+
+impl OuParams {
+    pub fn phi(&self) -> f64 {
+        2f64.powf(-1.0 / self.half_life)
+    }
+}
+
+// Synthetic trades following an OU price path, usable by every compute_*_bars function
+pub fn generate_ou_trades(params: &OuParams, num_trades: usize, tick_size: f64, seed: u64) -> Vec<Trade> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let noise = Normal::new(0.0, params.sigma).unwrap();
+    let amount_dist = Exp::new(20.0).unwrap(); // mean 0.05 per trade
+    let arrival_dist = Exp::new(1.0 / 100_000.0).unwrap(); // mean 100ms between trades
+
+    let phi = params.phi();
+    let mut price = params.initial_price;
+    let mut prev_price = price;
+    let mut timestamp: u64 = 1_704_067_200_000_000; // 2024-01-01 00:00:00 UTC
+
+    let mut trades = Vec::with_capacity(num_trades);
+    for id in 0..num_trades {
+        price = (1.0 - phi) * params.forecast + phi * price + noise.sample(&mut rng);
+        price = price.max(tick_size);
+        let quoted = (price / tick_size).round() * tick_size;
+
+        timestamp += arrival_dist.sample(&mut rng) as u64 + 1;
+        trades.push(Trade {
+            exchange: "synthetic".to_string(),
+            symbol: "OU".to_string(),
+            timestamp,
+            local_timestamp: timestamp,
+            id: id as u64,
+            side: if quoted >= prev_price { "buy" } else { "sell" }.to_string(),
+            price: quoted,
+            amount: amount_dist.sample(&mut rng),
+        });
+        prev_price = quoted;
+    }
+    trades
+}
+
+// Estimate phi and sigma by regressing (P_t - E0) on (P_{t-1} - E0). Errors when the fit is
+// not mean-reverting (phi outside (0, 1)), since the half-life is then undefined.
+pub fn fit_ou(prices: &[f64], forecast: f64) -> Result<OuParams, Box<dyn Error>> {
+    // Two residuals at least, for the residual std
+    if prices.len() < 3 {
+        return Err(format!("Fitting an OU process needs at least 3 prices, got {}", prices.len()).into());
+    }
+    let x: Vec<f64> = prices[..prices.len() - 1].iter().map(|p| p - forecast).collect();
+    let y: Vec<f64> = prices[1..].iter().map(|p| p - forecast).collect();
+
+    let sxx: f64 = x.iter().map(|v| v * v).sum();
+    let sxy: f64 = x.iter().zip(&y).map(|(a, b)| a * b).sum();
+    let phi = if sxx > 0.0 { sxy / sxx } else { 0.0 };
+    if !(phi > 0.0 && phi < 1.0) {
+        return Err(format!("Fitted phi = {:.6} is outside (0, 1): the prices are not mean-reverting", phi).into());
+    }
+
+    let residuals: Vec<f64> = x.iter().zip(&y).map(|(a, b)| b - phi * a).collect();
+    let n = residuals.len() as f64;
+    let mean = residuals.iter().sum::<f64>() / n;
+    let sigma = (residuals.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+
+    Ok(OuParams {
+        forecast,
+        half_life: -(2f64.ln()) / phi.ln(),
+        sigma,
+        initial_price: prices[0],
+    })
+}
+
+// Sharpe ratio of every (profit-take, stop-loss) pair in sigma units, simulated on the OU process.
+// Each path starts at 0 with long-run mean params.forecast (the expected gain per unit of sigma)
+// and is closed on hitting a barrier or after max_holding steps.
+pub fn optimal_trading_rule(
+    params: &OuParams,
+    pt_grid: &[f64],
+    sl_grid: &[f64],
+    num_paths: usize,
+    max_holding: usize,
+    seed: u64,
+) -> Vec<Vec<f64>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let noise = Normal::new(0.0, 1.0).unwrap();
+    let phi = params.phi();
+
+    pt_grid.iter().map(|&pt| {
+        sl_grid.iter().map(|&sl| {
+            let mut outcomes = Vec::with_capacity(num_paths);
+            for _ in 0..num_paths {
+                let mut p = 0.0;
+                let mut hp = 0;
+                loop {
+                    p = (1.0 - phi) * params.forecast + phi * p + noise.sample(&mut rng);
+                    hp += 1;
+                    if p > pt || p < -sl || hp > max_holding {
+                        outcomes.push(p);
+                        break;
+                    }
+                }
+            }
+            let n = outcomes.len() as f64;
+            let mean = outcomes.iter().sum::<f64>() / n;
+            let std = (outcomes.iter().map(|o| (o - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+            if std > 0.0 { mean / std } else { 0.0 }
+        }).collect()
+    }).collect()
+}
+
+pub fn run_synthetic_data() -> Result<(), Box<dyn Error>> {
+    // 1. Generate synthetic trades
+    let params = OuParams {
+        forecast: 60_000.0,
+        half_life: 20_000.0,
+        sigma: 5.0,
+        initial_price: 59_000.0,
+    };
+    let num_trades = 500_000;
+    let seed = 42;
+    println!(
+        "Generating {} OU trades (E0 = {}, half-life = {} trades, sigma = {}, seed = {})...",
+        num_trades, params.forecast, params.half_life, params.sigma, seed
+    );
+    let trades = generate_ou_trades(&params, num_trades, 0.1, seed);
+
+    // 2. Build bars with the existing builders
+    let tick_bars = compute_tick_bars(&trades, 1000);
+    println!("Generated {} tick bars.", tick_bars.len());
+    let total_dollar: f64 = trades.iter().map(dollar_metric).sum();
+    let dollar_bars = compute_dollar_bars(&trades, total_dollar / tick_bars.len() as f64);
+    println!("Generated {} dollar bars.", dollar_bars.len());
+
+    let h = return_std(&tick_bars);
+    let events = compute_cusum_events(&tick_bars, h);
+    println!("Detected {} CUSUM events on tick bars (h = {:.6}).", events.len(), h);
+
+    // 3. Recover the process parameters from the bars
+    let closes: Vec<f64> = tick_bars.iter().map(|b| b.close).collect();
+    let fitted = fit_ou(&closes, params.forecast)?;
+    println!(
+        "Fitted on tick bars: phi = {:.4}, half-life = {:.2} bars (true {:.2}), sigma = {:.4}",
+        fitted.phi(), fitted.half_life, params.half_life / 1000.0, fitted.sigma
+    );
+
+    // 4. Optimal trading rule: entry 2 sigma below the mean, fitted half-life
+    let rule_params = OuParams {
+        forecast: 2.0,
+        half_life: fitted.half_life,
+        sigma: 1.0,
+        initial_price: 0.0,
+    };
+    let grid: Vec<f64> = (1..=20).map(|i| i as f64 * 0.5).collect();
+    println!("Searching {}x{} profit-take / stop-loss grid...", grid.len(), grid.len());
+    let sharpe = optimal_trading_rule(&rule_params, &grid, &grid, 1000, 100, seed);
+
+    let mut best = (0, 0);
+    for (i, row) in sharpe.iter().enumerate() {
+        for (j, sr) in row.iter().enumerate() {
+            if *sr > sharpe[best.0][best.1] {
+                best = (i, j);
+            }
+        }
+    }
+    println!(
+        "Best rule: profit-take = {:.1} sigma, stop-loss = {:.1} sigma, Sharpe = {:.4}",
+        grid[best.0], grid[best.1], sharpe[best.0][best.1]
+    );
+
+    // 5. Draw Charts
+    std::fs::create_dir_all("src/ch13/result")?;
+    let bars_path = "src/ch13/result/synthetic_tick_bars.png";
+    draw_chart_file("Synthetic OU Tick Bar", &tick_bars, bars_path)?;
+
+    let output_path = "src/ch13/result/optimal_trading_rule.png";
+    println!("Drawing chart to {}...", output_path);
+    let title = format!("Sharpe Ratio (forecast = 2 sigma, half-life = {:.1} bars)", fitted.half_life);
+    draw_heatmap(&title, &grid, &grid, &sharpe, output_path)?;
+    println!("Chart saved to {}", output_path);
+
+    // Open file
+    #[cfg(target_os = "windows")]
+    std::process::Command::new("cmd")
+        .args(["/C", "start", output_path])
+        .spawn()?;
+
+    #[cfg(target_os = "macos")]
+    std::process::Command::new("open")
+        .arg(output_path)
+        .spawn()?;
+
+    #[cfg(target_os = "linux")]
+    std::process::Command::new("xdg-open")
+        .arg(output_path)
+        .spawn()?;
+
+    Ok(())
+}
+
+// values[i][j] is drawn at (x_grid[i], y_grid[j])
+pub fn draw_heatmap(
+    title: &str,
+    x_grid: &[f64],
+    y_grid: &[f64],
+    values: &[Vec<f64>],
+    output_path: &str,
+) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(output_path, (1024, 900)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let min_v = values.iter().flatten().copied().fold(f64::INFINITY, f64::min);
+    let max_v = values.iter().flatten().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = if max_v > min_v { max_v - min_v } else { 1.0 };
+
+    let dx = if x_grid.len() > 1 { x_grid[1] - x_grid[0] } else { 1.0 };
+    let dy = if y_grid.len() > 1 { y_grid[1] - y_grid[0] } else { 1.0 };
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption(format!("{} [{:.2}, {:.2}]", title, min_v, max_v), ("sans-serif", 26).into_font())
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d(
+            (x_grid[0] - dx / 2.0)..(x_grid[x_grid.len() - 1] + dx / 2.0),
+            (y_grid[0] - dy / 2.0)..(y_grid[y_grid.len() - 1] + dy / 2.0),
+        )?;
+
+    chart.configure_mesh()
+        .disable_mesh()
+        .x_desc("Profit-taking (sigma)")
+        .y_desc("Stop-loss (sigma)")
+        .draw()?;
+
+    chart.draw_series(
+        values.iter().enumerate().flat_map(|(i, row)| {
+            row.iter().enumerate().map(move |(j, v)| {
+                let t = (v - min_v) / range;
+                let color = HSLColor(0.66 * (1.0 - t), 0.8, 0.5); // blue (low) to red (high)
+                Rectangle::new(
+                    [(x_grid[i] - dx / 2.0, y_grid[j] - dy / 2.0), (x_grid[i] + dx / 2.0, y_grid[j] + dy / 2.0)],
+                    color.filled(),
+                )
+            })
+        })
+    )?;
+
+    Ok(())
+}
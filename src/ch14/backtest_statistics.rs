@@ -0,0 +1,405 @@
+use crate::config;
+use crate::base::{Bar, read_trades};
+use crate::base::stats::{norm_cdf, norm_ppf};
+use crate::backtest::engine::{BacktestConfig, BacktestResult, run_backtest_on_bars};
+use crate::ch2::compare_distribution::compute_stats;
+use crate::ch2::time_bar::compute_time_bars;
+use crate::ch10::bet_sizing::{build_cusum_bet_events, compute_bet_sizes, return_std};
+use std::error::Error;
+use std::fmt::Write as _;
+use chrono::{DateTime, Utc};
+use plotters::prelude::*;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestReport {
+    pub num_bars: usize,
+    pub num_bets: usize,
+    pub total_return: f64,
+    pub sharpe_ratio: f64, // per bar
+    pub annualized_sharpe_ratio: f64,
+    pub probabilistic_sharpe_ratio: f64,
+    pub deflated_sharpe_ratio: f64,
+    pub skewness: f64,
+    pub excess_kurtosis: f64,
+    pub max_drawdown: f64,
+    pub max_time_under_water_hours: f64,
+    pub hhi_positive: f64,
+    pub hhi_negative: f64,
+    pub avg_holding_period_hours: f64,
+    pub annualized_turnover: f64,
+    pub hit_ratio: f64,
+    pub implementation_shortfall_bps: f64,
+    pub return_on_execution_costs: f64,
+}
+
+// Simple returns of an equity series
+pub fn equity_returns(equity: &[f64]) -> Vec<f64> {
+    equity.windows(2)
+        .map(|w| if w[0] != 0.0 { w[1] / w[0] - 1.0 } else { 0.0 })
+        .collect()
+}
+
+// Non-annualized Sharpe ratio (mean / std of returns)
+pub fn sharpe_ratio(returns: &[f64]) -> f64 {
+    let n = returns.len() as f64;
+    if n < 2.0 {
+        return 0.0;
+    }
+    let mean = returns.iter().sum::<f64>() / n;
+    let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    if variance == 0.0 { 0.0 } else { mean / variance.sqrt() }
+}
+
+// Probability that the true Sharpe ratio exceeds sr_benchmark, given skewness and excess kurtosis
+// PSR = Phi((SR - SR*) sqrt(T - 1) / sqrt(1 - g3 SR + (g4 - 1) / 4 SR^2))
+pub fn probabilistic_sharpe_ratio(sr: f64, sr_benchmark: f64, num_obs: usize, skewness: f64, excess_kurtosis: f64) -> f64 {
+    if num_obs < 2 {
+        return 0.0;
+    }
+    let kurtosis = excess_kurtosis + 3.0;
+    let denom = 1.0 - skewness * sr + (kurtosis - 1.0) / 4.0 * sr * sr;
+    if denom <= 0.0 {
+        return 0.0;
+    }
+    norm_cdf((sr - sr_benchmark) * ((num_obs - 1) as f64).sqrt() / denom.sqrt())
+}
+
+// Expected maximum Sharpe ratio across num_trials independent trials with variance sr_variance
+pub fn expected_max_sharpe_ratio(num_trials: usize, sr_variance: f64) -> f64 {
+    if num_trials < 2 {
+        return 0.0;
+    }
+    let euler_gamma = 0.5772156649015329;
+    let n = num_trials as f64;
+    sr_variance.sqrt()
+        * ((1.0 - euler_gamma) * norm_ppf(1.0 - 1.0 / n)
+            + euler_gamma * norm_ppf(1.0 - 1.0 / (n * std::f64::consts::E)))
+}
+
+// PSR against the expected maximum Sharpe ratio under multiple testing
+pub fn deflated_sharpe_ratio(sr: f64, trial_srs: &[f64], num_obs: usize, skewness: f64, excess_kurtosis: f64) -> f64 {
+    let n = trial_srs.len() as f64;
+    let sr_variance = if n > 1.0 {
+        let mean = trial_srs.iter().sum::<f64>() / n;
+        trial_srs.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (n - 1.0)
+    } else {
+        0.0
+    };
+    let sr_benchmark = expected_max_sharpe_ratio(trial_srs.len(), sr_variance);
+    probabilistic_sharpe_ratio(sr, sr_benchmark, num_obs, skewness, excess_kurtosis)
+}
+
+// Drawdown (fraction of the high-water mark) at every point of an equity series
+pub fn drawdown_series(equity: &[f64]) -> Vec<f64> {
+    let mut hwm = f64::NEG_INFINITY;
+    equity.iter().map(|&e| {
+        hwm = hwm.max(e);
+        if hwm > 0.0 { 1.0 - e / hwm } else { 0.0 }
+    }).collect()
+}
+
+// Drawdown and time under water (hours) of every episode between high-water marks
+pub fn compute_dd_tuw(equity: &[f64], times: &[DateTime<Utc>]) -> Vec<(f64, f64)> {
+    let mut episodes = Vec::new();
+    if equity.is_empty() {
+        return episodes;
+    }
+
+    let mut hwm = equity[0];
+    let mut hwm_time = times[0];
+    let mut min_since_hwm = equity[0];
+
+    for (&e, &t) in equity.iter().zip(times).skip(1) {
+        if e > hwm {
+            if min_since_hwm < hwm {
+                let hours = (t - hwm_time).num_seconds() as f64 / 3600.0;
+                episodes.push((1.0 - min_since_hwm / hwm, hours));
+            }
+            hwm = e;
+            hwm_time = t;
+            min_since_hwm = e;
+        } else {
+            min_since_hwm = min_since_hwm.min(e);
+        }
+    }
+
+    // Still under water at the end of the sample
+    if min_since_hwm < hwm {
+        let hours = (*times.last().unwrap() - hwm_time).num_seconds() as f64 / 3600.0;
+        episodes.push((1.0 - min_since_hwm / hwm, hours));
+    }
+    episodes
+}
+
+// Normalized Herfindahl-Hirschman index of the returns' contributions (0 = even, 1 = concentrated)
+pub fn returns_hhi(returns: &[f64]) -> f64 {
+    let n = returns.len() as f64;
+    if n <= 2.0 {
+        return f64::NAN;
+    }
+    let total: f64 = returns.iter().sum();
+    let hhi: f64 = returns.iter().map(|r| (r / total).powi(2)).sum();
+    (hhi - 1.0 / n) / (1.0 - 1.0 / n)
+}
+
+// Average holding period (hours), weighting each closed unit by its size
+pub fn avg_holding_period(positions: &[f64], times: &[DateTime<Utc>]) -> f64 {
+    let mut entry_time = 0.0; // weighted average entry time, in hours
+    let mut sum_dt = 0.0;
+    let mut sum_w = 0.0;
+
+    for i in 1..positions.len() {
+        let t = (times[i] - times[0]).num_seconds() as f64 / 3600.0;
+        let (prev, curr) = (positions[i - 1], positions[i]);
+        let dp = curr - prev;
+        if dp == 0.0 {
+            continue;
+        }
+
+        if dp * prev >= 0.0 {
+            // Position increased (or opened)
+            if curr != 0.0 {
+                entry_time = (entry_time * prev + t * dp) / curr;
+            }
+        } else if curr * prev < 0.0 {
+            // Flipped: close everything, reopen at t
+            sum_dt += (t - entry_time) * prev.abs();
+            sum_w += prev.abs();
+            entry_time = t;
+        } else {
+            // Partially or fully reduced
+            sum_dt += (t - entry_time) * dp.abs();
+            sum_w += dp.abs();
+        }
+    }
+
+    if sum_w > 0.0 { sum_dt / sum_w } else { 0.0 }
+}
+
+// Indices where a bet ends: the position is flattened or flipped
+pub fn bet_timing(positions: &[f64]) -> Vec<usize> {
+    let mut ends = Vec::new();
+    for i in 1..positions.len() {
+        let (prev, curr) = (positions[i - 1], positions[i]);
+        if prev != 0.0 && (curr == 0.0 || curr * prev < 0.0) {
+            ends.push(i);
+        }
+    }
+    // Bet still open at the end of the sample
+    let last = positions.len().saturating_sub(1);
+    if positions.last().is_some_and(|p| *p != 0.0) && ends.last() != Some(&last) {
+        ends.push(last);
+    }
+    ends
+}
+
+pub fn compute_report(result: &BacktestResult, trial_srs: &[f64], periods_per_year: f64) -> BacktestReport {
+    let curve = &result.equity_curve;
+    let equity: Vec<f64> = curve.iter().map(|p| p.equity).collect();
+    let times: Vec<DateTime<Utc>> = curve.iter().map(|p| p.time).collect();
+    let positions: Vec<f64> = curve.iter().map(|p| p.position).collect();
+    let returns = equity_returns(&equity);
+
+    let stats = compute_stats(&returns);
+    let sr = sharpe_ratio(&returns);
+
+    let dd_tuw = compute_dd_tuw(&equity, &times);
+    let max_drawdown = dd_tuw.iter().map(|(dd, _)| *dd).fold(0.0, f64::max);
+    let max_tuw = dd_tuw.iter().map(|(_, tuw)| *tuw).fold(0.0, f64::max);
+
+    let positive: Vec<f64> = returns.iter().copied().filter(|r| *r > 0.0).collect();
+    let negative: Vec<f64> = returns.iter().copied().filter(|r| *r < 0.0).collect();
+
+    // Bet outcomes from equity changes between consecutive bet ends
+    let mut bet_pnls = Vec::new();
+    let mut start = positions.iter().position(|p| *p != 0.0).unwrap_or(0).saturating_sub(1);
+    for end in bet_timing(&positions) {
+        bet_pnls.push(equity[end] - equity[start]);
+        start = end;
+    }
+    let hit_ratio = if bet_pnls.is_empty() {
+        0.0
+    } else {
+        bet_pnls.iter().filter(|p| **p > 0.0).count() as f64 / bet_pnls.len() as f64
+    };
+
+    // Turnover and execution costs
+    let traded_notional: f64 = result.fills.iter().map(|f| (f.quantity * f.price).abs()).sum();
+    let fees: f64 = result.fills.iter().map(|f| f.fee).sum();
+    let shortfall: f64 = result.fills.iter().map(|f| f.quantity * (f.price - f.decision_price)).sum();
+    let mean_equity = equity.iter().sum::<f64>() / equity.len().max(1) as f64;
+    let years = returns.len() as f64 / periods_per_year;
+    let annualized_turnover = if years > 0.0 && mean_equity > 0.0 {
+        traded_notional / mean_equity / years
+    } else {
+        0.0
+    };
+    let implementation_shortfall_bps = if traded_notional > 0.0 {
+        (shortfall + fees) / traded_notional * 10_000.0
+    } else {
+        0.0
+    };
+    // Equity is net of fees and of fills away from the decision price; add both back
+    let net_pnl = equity.last().copied().unwrap_or(0.0) - equity.first().copied().unwrap_or(0.0);
+    let gross_pnl = net_pnl + fees + shortfall;
+    let return_on_execution_costs = if shortfall + fees > 0.0 { gross_pnl / (shortfall + fees) } else { 0.0 };
+
+    BacktestReport {
+        num_bars: curve.len(),
+        num_bets: bet_pnls.len(),
+        total_return: equity.last().unwrap_or(&0.0) / equity.first().unwrap_or(&1.0) - 1.0,
+        sharpe_ratio: sr,
+        annualized_sharpe_ratio: sr * periods_per_year.sqrt(),
+        probabilistic_sharpe_ratio: probabilistic_sharpe_ratio(sr, 0.0, returns.len(), stats.skewness, stats.kurtosis),
+        deflated_sharpe_ratio: deflated_sharpe_ratio(sr, trial_srs, returns.len(), stats.skewness, stats.kurtosis),
+        skewness: stats.skewness,
+        excess_kurtosis: stats.kurtosis,
+        max_drawdown,
+        max_time_under_water_hours: max_tuw,
+        hhi_positive: returns_hhi(&positive),
+        hhi_negative: returns_hhi(&negative),
+        avg_holding_period_hours: avg_holding_period(&positions, &times),
+        annualized_turnover,
+        hit_ratio,
+        implementation_shortfall_bps,
+        return_on_execution_costs,
+    }
+}
+
+pub fn report_to_markdown(report: &BacktestReport) -> String {
+    let rows = [
+        ("Bars", report.num_bars as f64),
+        ("Bets", report.num_bets as f64),
+        ("Total return", report.total_return),
+        ("Sharpe ratio (per bar)", report.sharpe_ratio),
+        ("Annualized Sharpe ratio", report.annualized_sharpe_ratio),
+        ("Probabilistic Sharpe ratio", report.probabilistic_sharpe_ratio),
+        ("Deflated Sharpe ratio", report.deflated_sharpe_ratio),
+        ("Skewness", report.skewness),
+        ("Excess kurtosis", report.excess_kurtosis),
+        ("Max drawdown", report.max_drawdown),
+        ("Max time under water (h)", report.max_time_under_water_hours),
+        ("HHI positive returns", report.hhi_positive),
+        ("HHI negative returns", report.hhi_negative),
+        ("Avg holding period (h)", report.avg_holding_period_hours),
+        ("Annualized turnover", report.annualized_turnover),
+        ("Hit ratio", report.hit_ratio),
+        ("Implementation shortfall (bps)", report.implementation_shortfall_bps),
+        ("Return on execution costs", report.return_on_execution_costs),
+    ];
+
+    let mut md = String::from("# Backtest Statistics\n\n| Metric | Value |\n|---|---|\n");
+    for (name, value) in rows {
+        let _ = writeln!(md, "| {} | {:.4} |", name, value);
+    }
+    md
+}
+
+// Bars per year implied by the average spacing of the bars (crypto trades 365 days)
+fn periods_per_year(bars: &[Bar]) -> f64 {
+    if bars.len() < 2 {
+        return 1.0;
+    }
+    let span = (bars.last().unwrap().time - bars[0].time).num_seconds() as f64;
+    let avg_seconds = span / (bars.len() - 1) as f64;
+    365.0 * 24.0 * 3600.0 / avg_seconds
+}
+
+pub fn run_backtest_statistics() -> Result<(), Box<dyn Error>> {
+    let file_path = config::TARDIS_CSV_PATH;
+    println!("Reading trades from {}...", file_path);
+    let trades = read_trades(file_path)?;
+    println!("Read {} trades.", trades.len());
+
+    // 1. Compute Time Bars (15 minutes)
+    let time_interval_minutes = 15;
+    println!("Computing {} minute time bars...", time_interval_minutes);
+    let bars = compute_time_bars(&trades, time_interval_minutes);
+    println!("Generated {} time bars.", bars.len());
+
+    // 2. Backtest every holding period; each one is a trial for the deflated Sharpe ratio
+    let h = return_std(&bars);
+    let cfg = BacktestConfig::default();
+    let hold_bars_grid = [1, 2, 4, 8, 16];
+    let mut results = Vec::new();
+    for &hold_bars in &hold_bars_grid {
+        let events = build_cusum_bet_events(&bars, h, hold_bars);
+        let targets = compute_bet_sizes(&bars, &events, 2, 0.05);
+        results.push(run_backtest_on_bars(&bars, &targets, &cfg));
+    }
+    let trial_srs: Vec<f64> = results.iter().map(|r| {
+        let equity: Vec<f64> = r.equity_curve.iter().map(|p| p.equity).collect();
+        sharpe_ratio(&equity_returns(&equity))
+    }).collect();
+    for (hold_bars, sr) in hold_bars_grid.iter().zip(&trial_srs) {
+        println!("Hold {:>2} bars: Sharpe = {:.4}", hold_bars, sr);
+    }
+
+    // 3. Report on the 4-bar strategy (1 hour), deflated by all trials
+    let result = &results[2];
+    let report = compute_report(result, &trial_srs, periods_per_year(&bars));
+    let markdown = report_to_markdown(&report);
+    println!("{}", markdown);
+
+    std::fs::create_dir_all("src/ch14/result")?;
+    let md_path = "src/ch14/result/backtest_report.md";
+    let json_path = "src/ch14/result/backtest_report.json";
+    std::fs::write(md_path, &markdown)?;
+    std::fs::write(json_path, serde_json::to_string_pretty(&report)?)?;
+    println!("Report saved to {} and {}", md_path, json_path);
+
+    // 4. Draw Chart
+    let output_path = "src/ch14/result/drawdown.png";
+    println!("Drawing chart to {}...", output_path);
+    draw_drawdown_chart(result, output_path)?;
+    println!("Chart saved to {}", output_path);
+
+    // Open file
+    #[cfg(target_os = "windows")]
+    std::process::Command::new("cmd")
+        .args(["/C", "start", output_path])
+        .spawn()?;
+
+    #[cfg(target_os = "macos")]
+    std::process::Command::new("open")
+        .arg(output_path)
+        .spawn()?;
+
+    #[cfg(target_os = "linux")]
+    std::process::Command::new("xdg-open")
+        .arg(output_path)
+        .spawn()?;
+
+    Ok(())
+}
+
+fn draw_drawdown_chart(result: &BacktestResult, output_path: &str) -> Result<(), Box<dyn Error>> {
+    let curve = &result.equity_curve;
+    let equity: Vec<f64> = curve.iter().map(|p| p.equity).collect();
+    let drawdown = drawdown_series(&equity);
+
+    let root = BitMapBackend::new(output_path, (1280, 768)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let min_time = curve.first().unwrap().time;
+    let max_time = curve.last().unwrap().time;
+    let max_dd = drawdown.iter().copied().fold(0.0, f64::max).max(1e-4);
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Drawdown (Underwater Curve)", ("sans-serif", 30).into_font())
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(60)
+        .build_cartesian_2d(min_time..max_time, (-max_dd * 100.0 * 1.1)..0.0)?;
+
+    chart.configure_mesh().y_desc("Drawdown (%)").draw()?;
+
+    chart.draw_series(AreaSeries::new(
+        curve.iter().zip(&drawdown).map(|(p, dd)| (p.time, -dd * 100.0)),
+        0.0,
+        RGBColor(209, 61, 61).mix(0.4),
+    ).border_style(RGBColor(209, 61, 61)))?;
+
+    Ok(())
+}
@@ -0,0 +1,3 @@
+pub mod backtest_statistics;
+
+pub use backtest_statistics::run_backtest_statistics;
@@ -0,0 +1,3 @@
+pub mod strategy_risk;
+
+pub use strategy_risk::run_strategy_risk;
@@ -0,0 +1,170 @@
+use crate::config;
+use crate::base::{Bar, Trade, read_trades};
+use crate::base::stats::norm_cdf;
+use crate::ch2::bar_builder::{dollar_metric, volume_metric};
+use crate::ch2::time_bar::compute_time_bars;
+use crate::ch2::tick_bar::compute_tick_bars;
+use crate::ch2::volume_bar::compute_volume_bars;
+use crate::ch2::dollar_bar::compute_dollar_bars;
+use crate::ch2::tick_imbalance_bar::compute_tick_imbalance_bars;
+use crate::ch2::volume_imbalance_bar::compute_volume_imbalance_bars;
+use crate::ch2::dollar_imbalance_bar::compute_dollar_imbalance_bars;
+use crate::ch2::cusum_filter::compute_cusum_event_indices;
+use crate::ch10::bet_sizing::return_std;
+use std::error::Error;
+
+// Annualized Sharpe ratio of a binary strategy paying pt with probability p and sl otherwise
+// theta = ((pt - sl) p + sl) / ((pt - sl) sqrt(p (1 - p))) * sqrt(freq)
+pub fn binary_sharpe_ratio(sl: f64, pt: f64, freq: f64, p: f64) -> f64 {
+    ((pt - sl) * p + sl) / ((pt - sl) * (p * (1.0 - p)).sqrt()) * freq.sqrt()
+}
+
+// Precision needed for a target Sharpe ratio with symmetric payouts (+pi / -pi)
+// p = 1/2 (1 + theta / sqrt(theta^2 + n))
+pub fn implied_precision_symmetric(freq: f64, target_sr: f64) -> f64 {
+    0.5 * (1.0 + target_sr / (target_sr * target_sr + freq).sqrt())
+}
+
+// Precision needed for a target Sharpe ratio with asymmetric payouts (sl < 0 < pt)
+pub fn implied_precision(sl: f64, pt: f64, freq: f64, target_sr: f64) -> Option<f64> {
+    let spread = pt - sl;
+    let a = (freq + target_sr * target_sr) * spread * spread;
+    let b = (2.0 * freq * sl - target_sr * target_sr * spread) * spread;
+    let c = freq * sl * sl;
+    let disc = b * b - 4.0 * a * c;
+    if disc < 0.0 {
+        return None;
+    }
+    let p = (-b + disc.sqrt()) / (2.0 * a);
+    if (0.0..=1.0).contains(&p) { Some(p) } else { None }
+}
+
+// Bets per year needed for a target Sharpe ratio given payouts and precision
+pub fn implied_frequency(sl: f64, pt: f64, p: f64, target_sr: f64) -> Option<f64> {
+    let freq = (target_sr * (pt - sl)).powi(2) * p * (1.0 - p) / ((pt - sl) * p + sl).powi(2);
+    // Squaring admits a spurious root when the expected payout is negative
+    if ((pt - sl) * p + sl) * target_sr >= 0.0 { Some(freq) } else { None }
+}
+
+#[derive(Debug, Clone)]
+pub struct StrategyRisk {
+    pub num_bets: usize,
+    pub precision: f64,
+    pub avg_profit: f64,
+    pub avg_loss: f64,
+    pub implied_sharpe_ratio: f64,
+    pub threshold_precision: Option<f64>,
+    pub prob_failure: f64,
+}
+
+// Probability that the strategy's precision falls below what the target Sharpe ratio needs.
+// The precision estimate is treated as normal with its standard error sqrt(p (1 - p) / n).
+pub fn probability_of_failure(bet_returns: &[f64], freq: f64, target_sr: f64) -> StrategyRisk {
+    let positive: Vec<f64> = bet_returns.iter().copied().filter(|r| *r > 0.0).collect();
+    let negative: Vec<f64> = bet_returns.iter().copied().filter(|r| *r <= 0.0).collect();
+    let n = bet_returns.len() as f64;
+
+    let mean = |v: &[f64]| if v.is_empty() { 0.0 } else { v.iter().sum::<f64>() / v.len() as f64 };
+    let pt = mean(&positive);
+    let sl = mean(&negative);
+    let p = positive.len() as f64 / n;
+
+    let threshold_precision = implied_precision(sl, pt, freq, target_sr);
+    let prob_failure = match threshold_precision {
+        Some(p_star) if p > 0.0 && p < 1.0 => norm_cdf((p_star - p) / (p * (1.0 - p) / n).sqrt()),
+        Some(p_star) => if p < p_star { 1.0 } else { 0.0 },
+        None => 1.0,
+    };
+
+    StrategyRisk {
+        num_bets: bet_returns.len(),
+        precision: p,
+        avg_profit: pt,
+        avg_loss: sl,
+        implied_sharpe_ratio: binary_sharpe_ratio(sl, pt, freq, p),
+        threshold_precision,
+        prob_failure,
+    }
+}
+
+// Outcome of betting on the direction of each CUSUM event until the next one:
+// side of the triggering move times the log return between the two events
+pub fn cusum_bet_returns(bars: &[Bar], h: f64) -> Vec<f64> {
+    let index = compute_cusum_event_indices(bars, h);
+    index.windows(2).map(|w| {
+        let (i, j) = (w[0], w[1]);
+        let side = (bars[i].close / bars[i - 1].close).ln().signum();
+        side * (bars[j].close / bars[i].close).ln()
+    }).collect()
+}
+
+// Bars of the requested type, sized to roughly match the number of 15 minute time bars
+pub fn build_bars(trades: &[Trade], bar_type: &str) -> Result<Vec<Bar>, Box<dyn Error>> {
+    let time_bars = compute_time_bars(trades, 15);
+    let num_time_bars = time_bars.len().max(1);
+
+    let total_volume: f64 = trades.iter().map(volume_metric).sum();
+    let total_dollar: f64 = trades.iter().map(dollar_metric).sum();
+
+    let bars = match bar_type {
+        "time" => time_bars,
+        "tick" => compute_tick_bars(trades, (trades.len() / num_time_bars).max(1)),
+        "volume" => compute_volume_bars(trades, total_volume / num_time_bars as f64),
+        "dollar" => compute_dollar_bars(trades, total_dollar / num_time_bars as f64),
+        "tick_imbalance" => compute_tick_imbalance_bars(trades, trades.len() as f64 / 1000.0),
+        "volume_imbalance" => compute_volume_imbalance_bars(trades, total_volume / 300.0),
+        "dollar_imbalance" => compute_dollar_imbalance_bars(trades, total_dollar / 300.0),
+        _ => return Err(format!("Unknown bar type: {}", bar_type).into()),
+    };
+    Ok(bars)
+}
+
+pub fn run_strategy_risk(bar_type: &str, threshold: Option<f64>) -> Result<(), Box<dyn Error>> {
+    let file_path = config::TARDIS_CSV_PATH;
+    println!("Reading trades from {}...", file_path);
+    let trades = read_trades(file_path)?;
+    println!("Read {} trades.", trades.len());
+
+    // 1. Bars
+    let bars = build_bars(&trades, bar_type)?;
+    println!("Generated {} {} bars.", bars.len(), bar_type);
+    if bars.len() < 3 {
+        return Err("Not enough bars".into());
+    }
+
+    // 2. Bet outcomes between CUSUM events
+    let h = threshold.unwrap_or_else(|| return_std(&bars));
+    let bet_returns = cusum_bet_returns(&bars, h);
+    println!("Derived {} bet outcomes from CUSUM events (h = {:.6}).", bet_returns.len(), h);
+    if bet_returns.len() < 2 {
+        return Err("Not enough CUSUM events to form bets".into());
+    }
+
+    // 3. Bets per year
+    let span_years = (bars.last().unwrap().time - bars[0].time).num_seconds() as f64 / (365.0 * 24.0 * 3600.0);
+    let freq = bet_returns.len() as f64 / span_years;
+    let target_sr = 2.0;
+
+    let risk = probability_of_failure(&bet_returns, freq, target_sr);
+
+    println!("--- Strategy Risk (target SR = {:.1}) ---", target_sr);
+    println!("Bets: {} ({:.1} per year)", risk.num_bets, freq);
+    println!("Precision: {:.4}", risk.precision);
+    println!("Average profit: {:.6}, average loss: {:.6}", risk.avg_profit, risk.avg_loss);
+    println!("Implied annualized Sharpe ratio: {:.4}", risk.implied_sharpe_ratio);
+    println!(
+        "Implied precision (symmetric payouts): {:.4}",
+        implied_precision_symmetric(freq, target_sr)
+    );
+    match risk.threshold_precision {
+        Some(p) => println!("Implied precision (observed payouts): {:.4}", p),
+        None => println!("Implied precision (observed payouts): unattainable"),
+    }
+    match implied_frequency(risk.avg_loss, risk.avg_profit, risk.precision, target_sr) {
+        Some(f) => println!("Implied betting frequency: {:.1} bets per year", f),
+        None => println!("Implied betting frequency: unattainable (negative expected payout)"),
+    }
+    println!("Probability of strategy failure: {:.4}", risk.prob_failure);
+
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use crate::config;
+use crate::base::{Bar, read_trades};
+use crate::base::stats::{anderson_darling_normal, autocorrelation, jarque_bera, ks_normal};
+use crate::ch2::bar_builder::dollar_metric;
+use crate::ch2::calibration::{BarKind, bar_count_stats, calibrate, trade_span};
+use crate::ch2::compare_distribution::{compute_log_returns, compute_stats};
+use crate::ch2::dynamic_dollar_bar::compute_dynamic_dollar_bars;
+use crate::ch2::range_bar::{RangeThreshold, average_time_bar_range, compute_range_bars};
+use crate::ch2::renko_bar::{average_time_bar_move, compute_renko_bars};
+use crate::ch2::time_bar::compute_time_bars;
+use crate::ch2::volatility_bar::{average_time_bar_variance, compute_volatility_bars};
+use std::error::Error;
+use std::fmt::Write as _;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BarQuality {
+    pub bar_type: String,
+    pub num_bars: usize,
+    pub count_mean: f64,      // bars per count period
+    pub count_cv: f64,        // std / mean of bars per count period (lower = more stable)
+    pub return_autocorr: f64, // lag-1 serial correlation of log returns
+    pub variance_of_variances: f64, // variance of the return variance in each variance period
+    pub skewness: f64,
+    pub excess_kurtosis: f64,
+    pub jb_stat: f64,
+    pub jb_pvalue: f64,
+    pub ks_stat: f64,
+    pub ks_pvalue: f64,
+    pub ad_stat: f64,
+    pub ad_pvalue: f64,
+}
+
+// Variance of the per-period variances of bar returns (periods from start with at least 2 returns)
+pub fn variance_of_variances(bars: &[Bar], start: DateTime<Utc>, period: Duration) -> f64 {
+    let period_secs = period.num_seconds().max(1);
+    let mut groups: Vec<Vec<f64>> = Vec::new();
+    for w in bars.windows(2) {
+        if w[0].close <= 0.0 || w[1].close <= 0.0 {
+            continue;
+        }
+        let k = ((w[1].time - start).num_seconds().max(0) / period_secs) as usize;
+        if groups.len() <= k {
+            groups.resize(k + 1, Vec::new());
+        }
+        groups[k].push((w[1].close / w[0].close).ln());
+    }
+
+    let sample_variance = |v: &[f64]| {
+        let n = v.len() as f64;
+        let mean = v.iter().sum::<f64>() / n;
+        v.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0)
+    };
+    let variances: Vec<f64> = groups.iter().filter(|g| g.len() >= 2).map(|g| sample_variance(g)).collect();
+    if variances.len() < 2 { 0.0 } else { sample_variance(&variances) }
+}
+
+// Counts and variances are bucketed over the trade span (start, end) shared by every bar type,
+// so a builder that stops emitting early shows empty periods instead of a shorter sample
+pub fn bar_quality(
+    name: &str,
+    bars: &[Bar],
+    (start, end): (DateTime<Utc>, DateTime<Utc>),
+    count_period: Duration,
+    variance_period: Duration,
+) -> BarQuality {
+    let returns = compute_log_returns(bars);
+    let stats = compute_stats(&returns);
+    let (count_mean, count_std) = bar_count_stats(bars, start, end, count_period);
+    let (jb_stat, jb_pvalue) = jarque_bera(&returns);
+    let (ks_stat, ks_pvalue) = ks_normal(&returns);
+    let (ad_stat, ad_pvalue) = anderson_darling_normal(&returns);
+
+    BarQuality {
+        bar_type: name.to_string(),
+        num_bars: bars.len(),
+        count_mean,
+        count_cv: if count_mean > 0.0 { count_std / count_mean } else { 0.0 },
+        return_autocorr: autocorrelation(&returns, 1),
+        variance_of_variances: variance_of_variances(bars, start, variance_period),
+        skewness: stats.skewness,
+        excess_kurtosis: stats.kurtosis,
+        jb_stat,
+        jb_pvalue,
+        ks_stat,
+        ks_pvalue,
+        ad_stat,
+        ad_pvalue,
+    }
+}
+
+pub fn quality_to_markdown(rows: &[BarQuality], count_period: &str, variance_period: &str) -> String {
+    let mut md = String::new();
+    let _ = writeln!(md, "# Bar Quality Report\n");
+    let _ = writeln!(md, "Bar counts per {}, return variances per {}.\n", count_period, variance_period);
+    let _ = writeln!(
+        md,
+        "| Bar type | Bars | Bars/period | Count CV | AC(1) | Var of vars | Skew | Ex. kurt | JB | JB p | KS | KS p | AD | AD p |"
+    );
+    let _ = writeln!(md, "|---|---:|---:|---:|---:|---:|---:|---:|---:|---:|---:|---:|---:|---:|");
+    for r in rows {
+        let _ = writeln!(
+            md,
+            "| {} | {} | {:.2} | {:.3} | {:.4} | {:.3e} | {:.3} | {:.3} | {:.2} | {:.4} | {:.4} | {:.4} | {:.3} | {:.4} |",
+            r.bar_type, r.num_bars, r.count_mean, r.count_cv, r.return_autocorr, r.variance_of_variances,
+            r.skewness, r.excess_kurtosis, r.jb_stat, r.jb_pvalue, r.ks_stat, r.ks_pvalue, r.ad_stat, r.ad_pvalue
+        );
+    }
+    md
+}
+
+pub fn run_bar_quality() -> Result<(), Box<dyn Error>> {
+    let file_path = config::TARDIS_CSV_PATH;
+    println!("Reading trades from {}...", file_path);
+    let trades = read_trades(file_path)?;
+    println!("Read {} trades.", trades.len());
+    if trades.len() < 2 {
+        return Err("Not enough trades".into());
+    }
+
+    // 1. Weekly counts and monthly variances as in the chapter 2 exercises,
+    //    hourly / 4-hourly when the data covers less than 8 weeks
+    let span_us = trades[trades.len() - 1].timestamp - trades[0].timestamp;
+    let (count_period, variance_period, count_name, variance_name) = if span_us >= 8 * 7 * 86_400_000_000 {
+        (Duration::weeks(1), Duration::days(30), "week", "month")
+    } else {
+        (Duration::hours(1), Duration::hours(4), "hour", "4 hours")
+    };
+
+    // 2. Every bar type, sized to the number of 15 minute time bars
+    let time_interval_minutes = 15;
+    let time_bars = compute_time_bars(&trades, time_interval_minutes);
+    let target = time_bars.len();
+    let period = Duration::minutes(time_interval_minutes);
+    println!("Building every bar type with about {} bars...", target);
+
+    let mut series: Vec<(String, Vec<Bar>)> = vec![("time".to_string(), time_bars)];
+    for kind in BarKind::ALL {
+        let calibration = calibrate(&trades, kind, target, period);
+        series.push((kind.name().to_string(), kind.build(&trades, calibration.threshold)));
+    }
+    let range = average_time_bar_range(&trades);
+    series.push(("range".to_string(), compute_range_bars(&trades, RangeThreshold::Fixed(range))));
+    series.push(("renko".to_string(), compute_renko_bars(&trades, average_time_bar_move(&trades))));
+    series.push(("volatility".to_string(), compute_volatility_bars(&trades, average_time_bar_variance(&trades))));
+    let total_dollar: f64 = trades.iter().map(dollar_metric).sum();
+    let (dynamic_bars, _) = compute_dynamic_dollar_bars(&trades, Duration::hours(1), 96.0, total_dollar / target.max(1) as f64);
+    series.push(("dynamic_dollar".to_string(), dynamic_bars));
+
+    // 3. Report
+    let span = trade_span(&trades).ok_or("Invalid trade timestamps")?;
+    let rows: Vec<BarQuality> = series.iter()
+        .map(|(name, bars)| bar_quality(name, bars, span, count_period, variance_period))
+        .collect();
+    let markdown = quality_to_markdown(&rows, count_name, variance_name);
+    println!("{}", markdown);
+
+    std::fs::create_dir_all("src/ch2/result")?;
+    let md_path = "src/ch2/result/bar_quality.md";
+    let csv_path = "src/ch2/result/bar_quality.csv";
+    std::fs::write(md_path, &markdown)?;
+    let mut wtr = csv::Writer::from_path(csv_path)?;
+    for row in &rows {
+        wtr.serialize(row)?;
+    }
+    wtr.flush()?;
+    println!("Report saved to {} and {}", md_path, csv_path);
+
+    Ok(())
+}
@@ -0,0 +1,279 @@
+use crate::config;
+use crate::base::{Bar, read_trades, Trade};
+use crate::base::instrument::registry;
+use crate::base::book::{BookSnapshot, Level, read_book_snapshots, read_quotes};
+use crate::base::order_book::{read_l2_updates, replay};
+use crate::ch2::time_bar::compute_time_bars;
+use std::error::Error;
+use std::path::Path;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use plotters::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rand_distr::{Distribution, Exp, Normal};
+
+#[derive(Debug, Clone)]
+pub struct BookFeatures {
+    pub timestamp: u64, // snapshot time, microseconds
+    pub mid: f64,
+    pub spread: f64,
+    pub relative_spread: f64, // spread / mid
+    pub imbalance: f64,       // depth-weighted (bid - ask) / (bid + ask), in [-1, 1]
+    pub microprice: f64,      // top-of-book size-weighted mid
+    pub bid_depth: f64,       // amount on the first `levels` bid levels
+    pub ask_depth: f64,
+}
+
+// Weighted imbalance of the first `levels` levels, level k weighted by exp(-decay * k)
+pub fn depth_imbalance(bids: &[Level], asks: &[Level], levels: usize, decay: f64) -> f64 {
+    let weighted = |side: &[Level]| -> f64 {
+        side.iter().take(levels).enumerate().map(|(k, l)| (-decay * k as f64).exp() * l.amount).sum()
+    };
+    let (bid, ask) = (weighted(bids), weighted(asks));
+    if bid + ask > 0.0 { (bid - ask) / (bid + ask) } else { 0.0 }
+}
+
+// Features of one snapshot; None when either side is empty
+pub fn snapshot_features(snapshot: &BookSnapshot, levels: usize, decay: f64) -> Option<BookFeatures> {
+    let bid = snapshot.best_bid()?;
+    let ask = snapshot.best_ask()?;
+    let mid = 0.5 * (bid.price + ask.price);
+    let spread = ask.price - bid.price;
+    let top = bid.amount + ask.amount;
+    let microprice = if top > 0.0 { (bid.price * ask.amount + ask.price * bid.amount) / top } else { mid };
+    Some(BookFeatures {
+        timestamp: snapshot.timestamp,
+        mid,
+        spread,
+        relative_spread: if mid > 0.0 { spread / mid } else { 0.0 },
+        imbalance: depth_imbalance(&snapshot.bids, &snapshot.asks, levels, decay),
+        microprice,
+        bid_depth: snapshot.bids.iter().take(levels).map(|l| l.amount).sum(),
+        ask_depth: snapshot.asks.iter().take(levels).map(|l| l.amount).sum(),
+    })
+}
+
+// As-of join: for each bar, the last features at or before bar.time + lag.
+// Use the bar interval as lag for bars stamped with their start (time bars, fixed bars)
+// and zero for bars stamped with their end (imbalance, run bars).
+pub fn align_to_bars(bars: &[Bar], features: &[BookFeatures], lag: Duration) -> Vec<Option<BookFeatures>> {
+    let lag_us = lag.num_microseconds().unwrap_or(0);
+    bars.iter()
+        .map(|bar| {
+            let cutoff = (bar.time.timestamp_micros() + lag_us).max(0) as u64;
+            let i = features.partition_point(|f| f.timestamp <= cutoff);
+            if i > 0 { Some(features[i - 1].clone()) } else { None }
+        })
+        .collect()
+}
+
+// Demo books only: one snapshot per second around the trade prices with a spread of 1-3 ticks
+// and random exponential depth. They carry no information, so features computed from them
+// say nothing about the market.
+pub fn synthetic_snapshots(trades: &[Trade], levels: usize, tick_size: f64, seed: u64) -> Vec<BookSnapshot> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let depth = Exp::new(1.0).unwrap();
+    let lean = Normal::new(0.0, 0.3).unwrap();
+    let mut snapshots = Vec::new();
+    let mut next_timestamp = 0;
+    for trade in trades {
+        if trade.timestamp < next_timestamp {
+            continue;
+        }
+        next_timestamp = trade.timestamp + 1_000_000;
+        let skew: f64 = lean.sample(&mut rng);
+        let half_ticks = rng.gen_range(1..=3) as f64;
+        let mid = (trade.price / tick_size).round() * tick_size;
+        let level = |k: usize, sign: f64, size: f64| Level {
+            price: mid + sign * tick_size * (half_ticks * 0.5 + k as f64),
+            amount: size,
+        };
+        let bids = (0..levels).map(|k| level(k, -1.0, depth.sample(&mut rng) * (1.0 + skew).max(0.1))).collect();
+        let asks = (0..levels).map(|k| level(k, 1.0, depth.sample(&mut rng) * (1.0 - skew).max(0.1))).collect();
+        snapshots.push(BookSnapshot {
+            exchange: trade.exchange.clone(),
+            symbol: trade.symbol.clone(),
+            timestamp: trade.timestamp,
+            local_timestamp: trade.local_timestamp,
+            bids,
+            asks,
+        });
+    }
+    snapshots
+}
+
+// Book snapshots replayed from L2 updates, recorded snapshots or top-of-book quotes,
+// whichever is available first; an error when there is no book data. L2 updates are replayed
+// to `levels` levels every `interval` microseconds, or after every batch when it is 0.
+pub fn load_snapshots(interval: u64, levels: usize) -> Result<Vec<BookSnapshot>, Box<dyn Error>> {
+    let snapshots = if Path::new(config::TARDIS_L2_PATH).exists() {
+        println!("Replaying L2 updates from {}...", config::TARDIS_L2_PATH);
+        let updates = read_l2_updates(config::TARDIS_L2_PATH)?;
+        let result = replay(&updates, interval, levels);
+        println!("Replayed {} updates; {} consistency issues.", updates.len(), result.issues.len());
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for issue in &result.issues {
+            match counts.iter_mut().find(|(name, _)| *name == issue.kind.name()) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((issue.kind.name(), 1)),
+            }
+        }
+        for (name, count) in counts {
+            println!("  {:<22} {}", name, count);
+        }
+        if let Some(issue) = result.issues.first() {
+            println!("  first at {}: {:?}", issue.timestamp, issue.kind);
+        }
+        result.snapshots
+    } else if Path::new(config::TARDIS_BOOK_PATH).exists() {
+        println!("Reading book snapshots from {}...", config::TARDIS_BOOK_PATH);
+        read_book_snapshots(config::TARDIS_BOOK_PATH)?
+    } else if Path::new(config::TARDIS_QUOTES_PATH).exists() {
+        println!("Reading quotes from {}...", config::TARDIS_QUOTES_PATH);
+        read_quotes(config::TARDIS_QUOTES_PATH)?.iter().map(BookSnapshot::from).collect()
+    } else {
+        return Err(format!(
+            "No book data: none of {}, {} or {} exists",
+            config::TARDIS_L2_PATH, config::TARDIS_BOOK_PATH, config::TARDIS_QUOTES_PATH
+        ).into());
+    };
+    Ok(snapshots)
+}
+
+pub fn run_book_features() -> Result<(), Box<dyn Error>> {
+    let file_path = config::TARDIS_CSV_PATH;
+    println!("Reading trades from {}...", file_path);
+    let trades = read_trades(file_path)?;
+    println!("Read {} trades.", trades.len());
+
+    let snapshots = load_snapshots(1_000_000, 25).map_err(|e| format!("{} (book_features_demo runs on synthetic books)", e))?;
+    book_features_report(&trades, &snapshots, false)
+}
+
+// The same pipeline on synthetic books, to try it without book data. Outputs are labeled
+// synthetic and the imbalance / return correlation is not reported.
+pub fn run_book_features_demo() -> Result<(), Box<dyn Error>> {
+    let file_path = config::TARDIS_CSV_PATH;
+    println!("Reading trades from {}...", file_path);
+    let trades = read_trades(file_path)?;
+    println!("Read {} trades.", trades.len());
+
+    println!("SYNTHETIC: building random 25-level books around the trade prices...");
+    let tick_size = trades.first().map(|t| registry().resolve(t).tick_size).filter(|t| *t > 0.0).unwrap_or(0.1);
+    let snapshots = synthetic_snapshots(&trades, 25, tick_size, 42);
+    book_features_report(&trades, &snapshots, true)
+}
+
+fn book_features_report(trades: &[Trade], snapshots: &[BookSnapshot], synthetic: bool) -> Result<(), Box<dyn Error>> {
+    // 1. Book snapshots
+    println!("{} snapshots.", snapshots.len());
+
+    let levels = 10;
+    let decay = 0.5;
+    let features: Vec<BookFeatures> = snapshots.iter().filter_map(|s| snapshot_features(s, levels, decay)).collect();
+
+    // 2. As-of features at the close of 5 minute time bars
+    let interval_minutes = 5;
+    let bars = compute_time_bars(trades, interval_minutes);
+    let aligned = align_to_bars(&bars, &features, Duration::minutes(interval_minutes));
+    println!("Aligned book features to {} time bars.", bars.len());
+
+    // Imbalance at the close against the next bar's return (meaningless on synthetic books)
+    let pairs: Vec<(f64, f64)> = aligned.iter().zip(bars.windows(2))
+        .filter_map(|(f, w)| Some((f.as_ref()?.imbalance, (w[1].close / w[0].close).ln())))
+        .collect();
+    let n = pairs.len() as f64;
+    if !synthetic && n > 2.0 {
+        let (mx, my) = (pairs.iter().map(|p| p.0).sum::<f64>() / n, pairs.iter().map(|p| p.1).sum::<f64>() / n);
+        let cov: f64 = pairs.iter().map(|p| (p.0 - mx) * (p.1 - my)).sum();
+        let vx: f64 = pairs.iter().map(|p| (p.0 - mx).powi(2)).sum();
+        let vy: f64 = pairs.iter().map(|p| (p.1 - my).powi(2)).sum();
+        println!("Correlation of closing imbalance with next bar return: {:.4}", cov / (vx * vy).sqrt());
+    }
+
+    std::fs::create_dir_all("src/ch2/result")?;
+    let suffix = if synthetic { "_synthetic" } else { "" };
+    let csv_path = format!("src/ch2/result/book_features{}.csv", suffix);
+    let mut wtr = csv::Writer::from_path(&csv_path)?;
+    wtr.write_record(["time", "close", "mid", "spread", "relative_spread", "imbalance", "microprice", "bid_depth", "ask_depth"])?;
+    for (bar, f) in bars.iter().zip(&aligned) {
+        let mut row = vec![bar.time.to_rfc3339(), bar.close.to_string()];
+        match f {
+            Some(f) => row.extend([f.mid, f.spread, f.relative_spread, f.imbalance, f.microprice, f.bid_depth, f.ask_depth].iter().map(|v| v.to_string())),
+            None => row.extend(std::iter::repeat_n(String::new(), 7)),
+        }
+        wtr.write_record(&row)?;
+    }
+    wtr.flush()?;
+    println!("Book features saved to {}", csv_path);
+
+    let output_path = format!("src/ch2/result/book_features{}.png", suffix);
+    let output_path = output_path.as_str();
+    println!("Drawing chart to {}...", output_path);
+    let title = if synthetic { "Book Features at Bar Close (SYNTHETIC BOOKS)" } else { "Book Features at Bar Close" };
+    draw_book_chart(title, &bars, &aligned, output_path)?;
+
+    // Try to open the file automatically
+    #[cfg(target_os = "windows")]
+    std::process::Command::new("cmd")
+        .args(["/C", "start", output_path])
+        .spawn()?;
+
+    #[cfg(target_os = "macos")]
+    std::process::Command::new("open")
+        .arg(output_path)
+        .spawn()?;
+
+    #[cfg(target_os = "linux")]
+    std::process::Command::new("xdg-open")
+        .arg(output_path)
+        .spawn()?;
+
+    Ok(())
+}
+
+fn draw_book_chart(title: &str, bars: &[Bar], aligned: &[Option<BookFeatures>], output_path: &str) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(output_path, (1024, 768)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let (upper, lower) = root.split_vertically(480);
+
+    let points: Vec<(DateTime<Utc>, &BookFeatures)> = aligned.iter()
+        .filter_map(|f| f.as_ref())
+        .map(|f| (Utc.timestamp_micros(f.timestamp as i64).unwrap(), f))
+        .collect();
+    let min_time = bars.first().ok_or("No bars")?.time;
+    let max_time = bars.last().unwrap().time + Duration::minutes(5);
+    let min_p = bars.iter().map(|b| b.low).fold(f64::INFINITY, f64::min);
+    let max_p = bars.iter().map(|b| b.high).fold(f64::NEG_INFINITY, f64::max);
+
+    let mut chart = ChartBuilder::on(&upper)
+        .caption(title, ("sans-serif", 30).into_font())
+        .margin(20)
+        .x_label_area_size(30)
+        .y_label_area_size(70)
+        .build_cartesian_2d(min_time..max_time, min_p..max_p)?;
+    chart.configure_mesh().y_desc("Price").draw()?;
+    chart.draw_series(LineSeries::new(points.iter().map(|(t, f)| (*t, f.mid)), &BLUE))?
+        .label("mid")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLUE));
+    chart.draw_series(LineSeries::new(points.iter().map(|(t, f)| (*t, f.microprice)), &RED))?
+        .label("microprice")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
+    chart.configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+
+    let mut chart = ChartBuilder::on(&lower)
+        .margin(20)
+        .x_label_area_size(30)
+        .y_label_area_size(70)
+        .build_cartesian_2d(min_time..max_time, -1.0..1.0)?;
+    chart.configure_mesh().y_desc("Depth imbalance").draw()?;
+    chart.draw_series(points.iter().map(|(t, f)| {
+        let color = if f.imbalance >= 0.0 { GREEN.mix(0.7) } else { RED.mix(0.7) };
+        Rectangle::new([(*t, 0.0), (*t + Duration::minutes(4), f.imbalance)], color.filled())
+    }))?;
+
+    Ok(())
+}
@@ -0,0 +1,203 @@
+use crate::base::Bar;
+use crate::base::stats::autocorrelation;
+use crate::ch2::compare_distribution::compute_stats;
+use chrono::{DateTime, Duration, Utc};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Statistic {
+    Mean,
+    Variance,
+    Skewness,
+    Kurtosis, // excess kurtosis
+    Autocorrelation(usize),
+}
+
+impl Statistic {
+    pub const ALL: [Statistic; 5] = [
+        Statistic::Mean,
+        Statistic::Variance,
+        Statistic::Skewness,
+        Statistic::Kurtosis,
+        Statistic::Autocorrelation(1),
+    ];
+
+    pub fn name(&self) -> String {
+        match self {
+            Statistic::Mean => "mean".to_string(),
+            Statistic::Variance => "variance".to_string(),
+            Statistic::Skewness => "skewness".to_string(),
+            Statistic::Kurtosis => "kurtosis".to_string(),
+            Statistic::Autocorrelation(lag) => format!("ac({})", lag),
+        }
+    }
+
+    pub fn compute(&self, data: &[f64]) -> f64 {
+        let n = data.len() as f64;
+        if n < 2.0 {
+            return 0.0;
+        }
+        let mean = data.iter().sum::<f64>() / n;
+        match self {
+            Statistic::Mean => mean,
+            Statistic::Variance => data.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0),
+            Statistic::Skewness => compute_stats(data).skewness,
+            Statistic::Kurtosis => compute_stats(data).kurtosis,
+            Statistic::Autocorrelation(lag) => autocorrelation(data, *lag),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfidenceInterval {
+    pub statistic: Statistic,
+    pub estimate: f64,
+    pub std_error: f64, // std of the bootstrap distribution
+    pub lower: f64,
+    pub upper: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct KurtosisTest {
+    pub kurtosis_a: f64,
+    pub kurtosis_b: f64,
+    pub difference: f64, // kurtosis_a - kurtosis_b
+    pub lower: f64,      // confidence interval of the difference
+    pub upper: f64,
+    pub p_value: f64, // H0: kurtosis_a >= kurtosis_b
+}
+
+// Rule-of-thumb block length n^(1/3)
+pub fn default_block_length(n: usize) -> usize {
+    ((n as f64).cbrt().round() as usize).max(1)
+}
+
+// Circular block bootstrap: blocks of block_len consecutive values starting at uniform
+// positions, wrapping around the end, until the resample has the original length
+pub fn block_resample<R: Rng>(data: &[f64], block_len: usize, rng: &mut R) -> Vec<f64> {
+    let n = data.len();
+    let mut sample = Vec::with_capacity(n);
+    if n == 0 {
+        return sample;
+    }
+    let block_len = block_len.clamp(1, n);
+    while sample.len() < n {
+        let start = rng.gen_range(0..n);
+        for k in 0..block_len.min(n - sample.len()) {
+            sample.push(data[(start + k) % n]);
+        }
+    }
+    sample
+}
+
+// Value at fraction q of sorted data (linear interpolation)
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let pos = q.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let i = pos.floor() as usize;
+    let j = (i + 1).min(sorted.len() - 1);
+    sorted[i] + (pos - i as f64) * (sorted[j] - sorted[i])
+}
+
+fn interval(statistic: Statistic, estimate: f64, mut draws: Vec<f64>, confidence: f64) -> ConfidenceInterval {
+    let n = draws.len().max(1) as f64;
+    let mean = draws.iter().sum::<f64>() / n;
+    let std_error = (draws.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0).max(1.0)).sqrt();
+    draws.sort_by(|a, b| a.total_cmp(b));
+    let alpha = 1.0 - confidence;
+    ConfidenceInterval {
+        statistic,
+        estimate,
+        std_error,
+        lower: quantile(&draws, alpha / 2.0),
+        upper: quantile(&draws, 1.0 - alpha / 2.0),
+    }
+}
+
+// Percentile intervals of every statistic from the same num_resamples block resamples
+pub fn bootstrap_confidence_intervals(
+    data: &[f64],
+    statistics: &[Statistic],
+    block_len: usize,
+    num_resamples: usize,
+    confidence: f64,
+    seed: u64,
+) -> Vec<ConfidenceInterval> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut draws = vec![Vec::with_capacity(num_resamples); statistics.len()];
+    for _ in 0..num_resamples {
+        let sample = block_resample(data, block_len, &mut rng);
+        for (d, s) in draws.iter_mut().zip(statistics) {
+            d.push(s.compute(&sample));
+        }
+    }
+    statistics.iter().zip(draws)
+        .map(|(s, d)| interval(*s, s.compute(data), d, confidence))
+        .collect()
+}
+
+// Log returns stamped with the time of the bar they end on
+pub fn timed_log_returns(bars: &[Bar]) -> Vec<(DateTime<Utc>, f64)> {
+    bars.windows(2)
+        .filter(|w| w[0].close > 0.0 && w[1].close > 0.0)
+        .map(|w| (w[1].time, (w[1].close / w[0].close).ln()))
+        .collect()
+}
+
+// Returns grouped into consecutive calendar blocks of block_secs from `start`
+fn time_blocks(returns: &[(DateTime<Utc>, f64)], start: DateTime<Utc>, block_secs: i64, num_blocks: usize) -> Vec<Vec<f64>> {
+    let mut blocks = vec![Vec::new(); num_blocks];
+    for (time, r) in returns {
+        let k = ((*time - start).num_seconds() / block_secs).clamp(0, num_blocks as i64 - 1) as usize;
+        blocks[k].push(*r);
+    }
+    blocks
+}
+
+// Paired block bootstrap of kurtosis(a) - kurtosis(b). Both series are resampled with
+// the same calendar blocks, so market conditions shared by the two bar types stay paired.
+// p_value is the bootstrap probability that the difference is not negative.
+pub fn paired_kurtosis_test(
+    a: &[(DateTime<Utc>, f64)],
+    b: &[(DateTime<Utc>, f64)],
+    block_len: Duration,
+    num_resamples: usize,
+    confidence: f64,
+    seed: u64,
+) -> KurtosisTest {
+    let kurtosis = |data: &[f64]| compute_stats(data).kurtosis;
+    let values = |series: &[(DateTime<Utc>, f64)]| series.iter().map(|(_, r)| *r).collect::<Vec<f64>>();
+    let kurtosis_a = kurtosis(&values(a));
+    let kurtosis_b = kurtosis(&values(b));
+    let difference = kurtosis_a - kurtosis_b;
+
+    let times = a.iter().chain(b).map(|(t, _)| *t);
+    let (Some(start), Some(end)) = (times.clone().min(), times.max()) else {
+        return KurtosisTest { kurtosis_a, kurtosis_b, difference, lower: difference, upper: difference, p_value: 1.0 };
+    };
+    let block_secs = block_len.num_seconds().max(1);
+    let num_blocks = ((end - start).num_seconds() / block_secs) as usize + 1;
+    let blocks_a = time_blocks(a, start, block_secs, num_blocks);
+    let blocks_b = time_blocks(b, start, block_secs, num_blocks);
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut draws = Vec::with_capacity(num_resamples);
+    for _ in 0..num_resamples {
+        let mut sample_a = Vec::with_capacity(a.len());
+        let mut sample_b = Vec::with_capacity(b.len());
+        for _ in 0..num_blocks {
+            let k = rng.gen_range(0..num_blocks);
+            sample_a.extend_from_slice(&blocks_a[k]);
+            sample_b.extend_from_slice(&blocks_b[k]);
+        }
+        draws.push(kurtosis(&sample_a) - kurtosis(&sample_b));
+    }
+
+    let not_lower = draws.iter().filter(|d| **d >= 0.0).count();
+    let p_value = (not_lower + 1) as f64 / (num_resamples + 1) as f64;
+    let ci = interval(Statistic::Kurtosis, difference, draws, confidence);
+    KurtosisTest { kurtosis_a, kurtosis_b, difference, lower: ci.lower, upper: ci.upper, p_value }
+}
@@ -0,0 +1,218 @@
+use crate::config;
+use crate::base::{Bar, read_trades, Trade};
+use crate::ch2::bar_builder::{dollar_metric, tick_metric, volume_metric};
+use crate::ch2::time_bar::compute_time_bars;
+use crate::ch2::tick_bar::compute_tick_bars;
+use crate::ch2::volume_bar::compute_volume_bars;
+use crate::ch2::dollar_bar::compute_dollar_bars;
+use crate::ch2::tick_imbalance_bar::compute_tick_imbalance_bars;
+use crate::ch2::volume_imbalance_bar::compute_volume_imbalance_bars;
+use crate::ch2::dollar_imbalance_bar::compute_dollar_imbalance_bars;
+use std::error::Error;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BarKind {
+    Tick,
+    Volume,
+    Dollar,
+    TickImbalance,
+    VolumeImbalance,
+    DollarImbalance,
+}
+
+impl BarKind {
+    pub const ALL: [BarKind; 6] = [
+        BarKind::Tick,
+        BarKind::Volume,
+        BarKind::Dollar,
+        BarKind::TickImbalance,
+        BarKind::VolumeImbalance,
+        BarKind::DollarImbalance,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BarKind::Tick => "tick",
+            BarKind::Volume => "volume",
+            BarKind::Dollar => "dollar",
+            BarKind::TickImbalance => "tick_imbalance",
+            BarKind::VolumeImbalance => "volume_imbalance",
+            BarKind::DollarImbalance => "dollar_imbalance",
+        }
+    }
+
+    // Threshold of fixed bars; initial expected size of imbalance bars
+    pub fn build(&self, trades: &[Trade], threshold: f64) -> Vec<Bar> {
+        match self {
+            BarKind::Tick => compute_tick_bars(trades, (threshold.round() as usize).max(1)),
+            BarKind::Volume => compute_volume_bars(trades, threshold),
+            BarKind::Dollar => compute_dollar_bars(trades, threshold),
+            BarKind::TickImbalance => compute_tick_imbalance_bars(trades, threshold),
+            BarKind::VolumeImbalance => compute_volume_imbalance_bars(trades, threshold),
+            BarKind::DollarImbalance => compute_dollar_imbalance_bars(trades, threshold),
+        }
+    }
+
+    fn total_metric(&self, trades: &[Trade]) -> f64 {
+        let metric = match self {
+            BarKind::Tick | BarKind::TickImbalance => tick_metric,
+            BarKind::Volume | BarKind::VolumeImbalance => volume_metric,
+            BarKind::Dollar | BarKind::DollarImbalance => dollar_metric,
+        };
+        trades.iter().map(metric).sum()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Calibration {
+    pub kind: BarKind,
+    pub threshold: f64,
+    pub num_bars: usize,
+    pub target_bars: usize,
+    pub bars_per_period: f64,     // mean number of bars per period
+    pub bars_per_period_std: f64, // std of the number of bars per period
+    pub reached: bool,            // bar count within TARGET_TOLERANCE of the target
+}
+
+// Relative miss of the target bar count still reported as reached
+pub const TARGET_TOLERANCE: f64 = 0.05;
+
+// Time of the first and last trade
+pub fn trade_span(trades: &[Trade]) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let first = Utc.timestamp_micros(trades.first()?.timestamp as i64).single()?;
+    let last = Utc.timestamp_micros(trades.last()?.timestamp as i64).single()?;
+    Some((first, last))
+}
+
+// Mean and std of the number of bars stamped in each period from start to end, so that
+// periods after a builder stops emitting count as empty
+pub fn bar_count_stats(bars: &[Bar], start: DateTime<Utc>, end: DateTime<Utc>, period: Duration) -> (f64, f64) {
+    let period_secs = period.num_seconds().max(1);
+    let num_periods = ((end - start).num_seconds().max(0) / period_secs) as usize + 1;
+    let mut counts = vec![0.0; num_periods];
+    for bar in bars {
+        let k = ((bar.time - start).num_seconds().max(0) / period_secs) as usize;
+        counts[k.min(num_periods - 1)] += 1.0;
+    }
+    let n = counts.len() as f64;
+    let mean = counts.iter().sum::<f64>() / n;
+    let std = if n > 1.0 {
+        (counts.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+    } else {
+        0.0
+    };
+    (mean, std)
+}
+
+// Geometric bisection on the threshold so that the bar count is as close as possible
+// to target_bars. Bar counts fall as the threshold rises; imbalance bars adapt their
+// threshold and may not reach every target, so the closest threshold found is returned.
+pub fn calibrate(trades: &[Trade], kind: BarKind, target_bars: usize, period: Duration) -> Calibration {
+    let total = kind.total_metric(trades).max(f64::MIN_POSITIVE);
+    let target = target_bars.max(1);
+    let mut lo = total / (target as f64 * 1000.0);
+    let mut hi = total;
+
+    let mut best_threshold = total / target as f64;
+    let mut best_bars = kind.build(trades, best_threshold);
+
+    for _ in 0..60 {
+        let mid = (lo * hi).sqrt();
+        let bars = kind.build(trades, mid);
+        if bars.len().abs_diff(target) < best_bars.len().abs_diff(target) {
+            best_threshold = mid;
+            best_bars = bars.clone();
+        }
+        if bars.len() == target || hi / lo < 1.0 + 1e-9 {
+            break;
+        }
+        if bars.len() > target {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    let (bars_per_period, bars_per_period_std) = match trade_span(trades) {
+        Some((start, end)) => bar_count_stats(&best_bars, start, end, period),
+        None => (0.0, 0.0),
+    };
+    Calibration {
+        kind,
+        threshold: best_threshold,
+        num_bars: best_bars.len(),
+        target_bars: target,
+        bars_per_period,
+        bars_per_period_std,
+        reached: best_bars.len().abs_diff(target) as f64 <= TARGET_TOLERANCE * target as f64,
+    }
+}
+
+// Target count for an average of bars_per_day over the span of the trades
+pub fn target_from_bars_per_day(trades: &[Trade], bars_per_day: f64) -> usize {
+    let (Some(first), Some(last)) = (trades.first(), trades.last()) else {
+        return 0;
+    };
+    let days = (last.timestamp - first.timestamp) as f64 / 86_400_000_000.0;
+    (days * bars_per_day).round().max(1.0) as usize
+}
+
+pub fn run_calibration() -> Result<(), Box<dyn Error>> {
+    let file_path = config::TARDIS_CSV_PATH;
+    println!("Reading trades from {}...", file_path);
+    let trades = read_trades(file_path)?;
+    println!("Read {} trades.", trades.len());
+
+    // 1. Target: as many bars as 15 minute time bars (96 per day)
+    let time_interval_minutes = 15;
+    let time_bars = compute_time_bars(&trades, time_interval_minutes);
+    let period = Duration::minutes(time_interval_minutes);
+    let target = target_from_bars_per_day(&trades, 24.0 * 60.0 / time_interval_minutes as f64);
+    println!(
+        "Generated {} time bars; calibrating every bar type to {} bars ({} per day)...",
+        time_bars.len(), target, 24 * 60 / time_interval_minutes
+    );
+
+    // 2. Calibrate
+    let calibrations: Vec<Calibration> = BarKind::ALL.iter()
+        .map(|&kind| calibrate(&trades, kind, target, period))
+        .collect();
+
+    println!("--- Calibration (per {} minute period) ---", time_interval_minutes);
+    println!("{:<17} {:>16} {:>6} {:>8} {:>8}  target", "bar type", "threshold", "bars", "mean", "std");
+    for c in &calibrations {
+        println!(
+            "{:<17} {:>16.4} {:>6} {:>8.3} {:>8.3}  {}",
+            c.kind.name(), c.threshold, c.num_bars, c.bars_per_period, c.bars_per_period_std,
+            if c.reached { "reached" } else { "MISSED" }
+        );
+    }
+    for c in calibrations.iter().filter(|c| !c.reached) {
+        println!(
+            "Warning: no {} threshold reaches the target of {} bars (closest: {} bars).",
+            c.kind.name(), c.target_bars, c.num_bars
+        );
+    }
+
+    // 3. Save
+    std::fs::create_dir_all("src/ch2/result")?;
+    let csv_path = "src/ch2/result/calibration.csv";
+    let mut wtr = csv::Writer::from_path(csv_path)?;
+    wtr.write_record(["bar_type", "threshold", "num_bars", "target_bars", "bars_per_period", "bars_per_period_std", "reached"])?;
+    for c in &calibrations {
+        wtr.write_record([
+            c.kind.name().to_string(),
+            c.threshold.to_string(),
+            c.num_bars.to_string(),
+            c.target_bars.to_string(),
+            c.bars_per_period.to_string(),
+            c.bars_per_period_std.to_string(),
+            c.reached.to_string(),
+        ])?;
+    }
+    wtr.flush()?;
+    println!("Calibration saved to {}", csv_path);
+
+    Ok(())
+}
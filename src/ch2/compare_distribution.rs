@@ -1,18 +1,24 @@
 use crate::config;
 use crate::base::{read_trades, Bar};
 use crate::ch2::time_bar::compute_time_bars;
-use crate::ch2::tick_bar::compute_tick_bars;
-use crate::ch2::volume_bar::compute_volume_bars;
-use crate::ch2::dollar_bar::compute_dollar_bars;
+use crate::ch2::bar_builder::{ClosingRule, compute_information_bars, dollar_metric};
+use crate::ch2::dynamic_dollar_bar::compute_dynamic_dollar_bars;
+use crate::ch2::calibration::{BarKind, calibrate};
+use crate::ch2::range_bar::{RangeThreshold, average_time_bar_range, compute_range_bars};
+use crate::ch2::renko_bar::{average_time_bar_move, compute_renko_bars};
+use crate::ch2::volatility_bar::{average_time_bar_variance, compute_volatility_bars};
+use crate::ch2::bootstrap::{Statistic, bootstrap_confidence_intervals, default_block_length, paired_kurtosis_test, timed_log_returns};
+use crate::ch2::distribution_charts::{QqReference, ReturnSeries, draw_acf_chart, draw_kde_chart, draw_qq_chart};
 use std::error::Error;
+use chrono::Duration;
 use plotters::prelude::*;
 
-struct Stats {
-    skewness: f64,
-    kurtosis: f64,
+pub struct Stats {
+    pub skewness: f64,
+    pub kurtosis: f64, // excess kurtosis
 }
 
-fn compute_stats(data: &[f64]) -> Stats {
+pub fn compute_stats(data: &[f64]) -> Stats {
     let n = data.len() as f64;
     if n < 4.0 { return Stats { skewness: 0.0, kurtosis: 0.0 }; }
     
@@ -39,7 +45,7 @@ fn compute_stats(data: &[f64]) -> Stats {
     }
 }
 
-fn compute_log_returns(bars: &[Bar]) -> Vec<f64> {
+pub fn compute_log_returns(bars: &[Bar]) -> Vec<f64> {
     let mut returns = Vec::new();
     for i in 1..bars.len() {
         let prev_close = bars[i - 1].close;
@@ -52,7 +58,7 @@ fn compute_log_returns(bars: &[Bar]) -> Vec<f64> {
     returns
 }
 
-fn standardize(data: &[f64]) -> Vec<f64> {
+pub fn standardize(data: &[f64]) -> Vec<f64> {
     let n = data.len() as f64;
     if n <= 1.0 { return data.to_vec(); }
     let mean = data.iter().sum::<f64>() / n;
@@ -75,91 +81,140 @@ pub fn run_compare_distribution() -> Result<(), Box<dyn Error>> {
     println!("Generated {} time bars.", time_bars.len());
     let num_time_bars = time_bars.len();
 
-    // 2. Compute Tick Bars
-    let total_trades = trades.len();
-    let tick_interval = if num_time_bars > 0 {
-        total_trades / num_time_bars
-    } else {
-        1000 // fallback
-    };
-    println!("Computing {} tick bars...", tick_interval);
-    let tick_bars = compute_tick_bars(&trades, tick_interval);
-    println!("Generated {} tick bars.", tick_bars.len());
-
-    // 3. Compute Volume Bars
-    let total_volume: f64 = trades.iter().map(|t| t.amount).sum();
-    let volume_interval = if num_time_bars > 0 {
-        total_volume / num_time_bars as f64
-    } else {
-        1000.0
+    // 2-4. Tick, Volume, Dollar and Imbalance Bars calibrated to the number of time bars
+    let period = Duration::minutes(time_interval_minutes);
+    let calibrated_bars = |kind: BarKind| {
+        let calibration = calibrate(&trades, kind, num_time_bars, period);
+        println!(
+            "Calibrated {} bars: threshold {:.2}, {} bars ({:.2} +/- {:.2} per time bar)",
+            kind.name(), calibration.threshold, calibration.num_bars,
+            calibration.bars_per_period, calibration.bars_per_period_std
+        );
+        kind.build(&trades, calibration.threshold)
     };
-    println!("Computing {:.2} volume bars...", volume_interval);
-    let volume_bars = compute_volume_bars(&trades, volume_interval);
-    println!("Generated {} volume bars.", volume_bars.len());
-
-    // 4. Compute Dollar Bars
-    let total_dollar: f64 = trades.iter().map(|t| t.price * t.amount).sum();
-    let dollar_interval = if num_time_bars > 0 {
-        total_dollar / num_time_bars as f64
-    } else {
-        10000.0
-    };
-    println!("Computing {:.2} dollar bars...", dollar_interval);
-    let dollar_bars = compute_dollar_bars(&trades, dollar_interval);
-    println!("Generated {} dollar bars.", dollar_bars.len());
-
-    // 5. Compute Returns & Stats
-    let time_returns = compute_log_returns(&time_bars);
-    let tick_returns = compute_log_returns(&tick_bars);
-    let volume_returns = compute_log_returns(&volume_bars);
-    let dollar_returns = compute_log_returns(&dollar_bars);
-    
-    let time_stats = compute_stats(&time_returns);
-    let tick_stats = compute_stats(&tick_returns);
-    let volume_stats = compute_stats(&volume_returns);
-    let dollar_stats = compute_stats(&dollar_returns);
+    let tick_bars = calibrated_bars(BarKind::Tick);
+    let volume_bars = calibrated_bars(BarKind::Volume);
+    let dollar_bars = calibrated_bars(BarKind::Dollar);
+    let tick_imbalance_bars = calibrated_bars(BarKind::TickImbalance);
+    let volume_imbalance_bars = calibrated_bars(BarKind::VolumeImbalance);
+    let dollar_imbalance_bars = calibrated_bars(BarKind::DollarImbalance);
+
+    // Dollar run bars as in draw_run_bar, dynamic dollar bars sized to the time bars
+    let run_rule = ClosingRule::Run { initial_expected_ticks: trades.len() as f64 / 1000.0, alpha: 0.01 };
+    let run_bars = compute_information_bars(&trades, dollar_metric, run_rule);
+    println!("Generated {} dollar run bars.", run_bars.len());
+    let total_dollar: f64 = trades.iter().map(dollar_metric).sum();
+    let (dynamic_dollar_bars, _) =
+        compute_dynamic_dollar_bars(&trades, Duration::hours(1), 96.0, total_dollar / num_time_bars.max(1) as f64);
+    println!("Generated {} dynamic dollar bars.", dynamic_dollar_bars.len());
+
+    // 5. Compute Price-Driven Bars (range, renko, volatility)
+    let range = average_time_bar_range(&trades);
+    println!("Computing {:.2} range bars...", range);
+    let range_bars = compute_range_bars(&trades, RangeThreshold::Fixed(range));
+    println!("Generated {} range bars.", range_bars.len());
+
+    let brick_size = average_time_bar_move(&trades);
+    println!("Computing {:.2} renko bricks...", brick_size);
+    let renko_bars = compute_renko_bars(&trades, brick_size);
+    println!("Generated {} renko bricks.", renko_bars.len());
+
+    let variance_threshold = average_time_bar_variance(&trades);
+    println!("Computing {:.3e} volatility bars...", variance_threshold);
+    let volatility_bars = compute_volatility_bars(&trades, variance_threshold);
+    println!("Generated {} volatility bars.", volatility_bars.len());
+
+    // 6. Compute Returns & Stats
+    let bar_sets = [
+        ("Time", &time_bars),
+        ("Tick", &tick_bars),
+        ("Volume", &volume_bars),
+        ("Dollar", &dollar_bars),
+        ("Tick Imbalance", &tick_imbalance_bars),
+        ("Volume Imbalance", &volume_imbalance_bars),
+        ("Dollar Imbalance", &dollar_imbalance_bars),
+        ("Dollar Run", &run_bars),
+        ("Dynamic Dollar", &dynamic_dollar_bars),
+        ("Range", &range_bars),
+        ("Renko", &renko_bars),
+        ("Volatility", &volatility_bars),
+    ];
+    let series: Vec<ReturnSeries> = bar_sets.iter()
+        .map(|(name, bars)| ReturnSeries { name: name.to_string(), returns: compute_log_returns(bars) })
+        .collect();
+    let stats: Vec<Stats> = series.iter().map(|s| compute_stats(&s.returns)).collect();
 
     println!("--- Statistics ---");
-    println!("Time Bar: Skewness={:.4}, Excess Kurtosis={:.4}", time_stats.skewness, time_stats.kurtosis);
-    println!("Tick Bar: Skewness={:.4}, Excess Kurtosis={:.4}", tick_stats.skewness, tick_stats.kurtosis);
-    println!("Volume Bar: Skewness={:.4}, Excess Kurtosis={:.4}", volume_stats.skewness, volume_stats.kurtosis);
-    println!("Dollar Bar: Skewness={:.4}, Excess Kurtosis={:.4}", dollar_stats.skewness, dollar_stats.kurtosis);
-
-    let time_std = standardize(&time_returns);
-    let tick_std = standardize(&tick_returns);
-    let volume_std = standardize(&volume_returns);
-    let dollar_std = standardize(&dollar_returns);
-
-    // 6. Draw Histograms
-    let output_tick = "src/ch2/result/compare_time_vs_tick.png";
-    draw_distribution_chart(&time_std, &tick_std, &time_stats, &tick_stats, "Time Bar", "Tick Bar", output_tick)?;
-    
-    let output_volume = "src/ch2/result/compare_time_vs_volume.png";
-    draw_distribution_chart(&time_std, &volume_std, &time_stats, &volume_stats, "Time Bar", "Volume Bar", output_volume)?;
+    for (s, st) in series.iter().zip(&stats) {
+        println!("{} Bar: Skewness={:.4}, Excess Kurtosis={:.4}", s.name, st.skewness, st.kurtosis);
+    }
+
+    // 7. Block-bootstrap confidence intervals and paired kurtosis tests against time bars
+    let num_resamples = 1000;
+    let confidence = 0.95;
+    println!("--- Block Bootstrap ({} resamples, {:.0}% intervals) ---", num_resamples, confidence * 100.0);
+    println!("{:<16} {:<9} {:>12} {:>12} {:>12} {:>12}", "bar type", "statistic", "estimate", "std error", "lower", "upper");
+    for s in &series {
+        let block_len = default_block_length(s.returns.len());
+        for ci in bootstrap_confidence_intervals(&s.returns, &Statistic::ALL, block_len, num_resamples, confidence, 42) {
+            println!(
+                "{:<16} {:<9} {:>12.4e} {:>12.4e} {:>12.4e} {:>12.4e}",
+                s.name, ci.statistic.name(), ci.estimate, ci.std_error, ci.lower, ci.upper
+            );
+        }
+    }
+
+    let block_duration = period * 4;
+    println!("--- Paired kurtosis test vs Time Bar ({} min blocks, H0: kurtosis not lower) ---", block_duration.num_minutes());
+    let time_timed = timed_log_returns(&time_bars);
+    for (name, bars) in bar_sets.iter().skip(1) {
+        let test = paired_kurtosis_test(&timed_log_returns(bars), &time_timed, block_duration, num_resamples, confidence, 42);
+        println!(
+            "{:<16} K={:.4} vs {:.4}, diff={:.4} [{:.4}, {:.4}], p={:.4}",
+            name, test.kurtosis_a, test.kurtosis_b, test.difference, test.lower, test.upper, test.p_value
+        );
+    }
+
+    // 8. Draw Histograms of every bar type against time bars
+    std::fs::create_dir_all("src/ch2/result")?;
+    let time_std = standardize(&series[0].returns);
+    for (s, st) in series.iter().zip(&stats).skip(1) {
+        let output_path = format!("src/ch2/result/compare_time_vs_{}.png", s.name.to_lowercase().replace(' ', "_"));
+        let label = format!("{} Bar", s.name);
+        draw_distribution_chart(&time_std, &standardize(&s.returns), &stats[0], st, "Time Bar", &label, &output_path)?;
+    }
 
-    let output_dollar = "src/ch2/result/compare_time_vs_dollar.png";
-    draw_distribution_chart(&time_std, &dollar_std, &time_stats, &dollar_stats, "Time Bar", "Dollar Bar", output_dollar)?;
+    // 9. Draw KDE, QQ and ACF/PACF charts of all bar types
+    let output_kde = "src/ch2/result/compare_kde.png";
+    draw_kde_chart(&series, output_kde)?;
+    let output_qq = "src/ch2/result/compare_qq_normal.png";
+    draw_qq_chart(&series, QqReference::Normal, output_qq)?;
+    draw_qq_chart(&series, QqReference::StudentT, "src/ch2/result/compare_qq_student_t.png")?;
+    let output_acf = "src/ch2/result/compare_acf_returns.png";
+    draw_acf_chart(&series, 20, false, output_acf)?;
+    draw_acf_chart(&series, 20, true, "src/ch2/result/compare_acf_squared_returns.png")?;
+    println!("Charts saved to src/ch2/result/");
 
     // Open files
     #[cfg(target_os = "windows")]
     {
-        std::process::Command::new("cmd").args(&["/C", "start", output_tick]).spawn()?;
-        std::process::Command::new("cmd").args(&["/C", "start", output_volume]).spawn()?;
-        std::process::Command::new("cmd").args(&["/C", "start", output_dollar]).spawn()?;
+        std::process::Command::new("cmd").args(["/C", "start", output_kde]).spawn()?;
+        std::process::Command::new("cmd").args(["/C", "start", output_qq]).spawn()?;
+        std::process::Command::new("cmd").args(["/C", "start", output_acf]).spawn()?;
     }
 
     #[cfg(target_os = "macos")]
     {
-        std::process::Command::new("open").arg(output_tick).spawn()?;
-        std::process::Command::new("open").arg(output_volume).spawn()?;
-        std::process::Command::new("open").arg(output_dollar).spawn()?;
+        std::process::Command::new("open").arg(output_kde).spawn()?;
+        std::process::Command::new("open").arg(output_qq).spawn()?;
+        std::process::Command::new("open").arg(output_acf).spawn()?;
     }
 
     #[cfg(target_os = "linux")]
     {
-        std::process::Command::new("xdg-open").arg(output_tick).spawn()?;
-        std::process::Command::new("xdg-open").arg(output_volume).spawn()?;
-        std::process::Command::new("xdg-open").arg(output_dollar).spawn()?;
+        std::process::Command::new("xdg-open").arg(output_kde).spawn()?;
+        std::process::Command::new("xdg-open").arg(output_qq).spawn()?;
+        std::process::Command::new("xdg-open").arg(output_acf).spawn()?;
     }
 
     Ok(())
@@ -195,7 +250,7 @@ fn draw_distribution_chart(
     } else {
         50
     };
-    let bins = bins.max(20).min(200); // Clamp to reasonable range
+    let bins = bins.clamp(20, 200); // Clamp to reasonable range
     
     let step = (max_x - min_x) / bins as f64;
     
@@ -274,11 +329,11 @@ fn draw_distribution_chart(
     // Gaussian (Black, Dashed)
     chart.draw_series(LineSeries::new(gaussian, &BLACK.mix(0.8)))?
         .label("Normal Dist")
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], &BLACK));
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLACK));
 
     chart.configure_series_labels()
-        .background_style(&WHITE.mix(0.8))
-        .border_style(&BLACK)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
         .draw()?;
 
     Ok(())
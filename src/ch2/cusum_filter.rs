@@ -1,6 +1,7 @@
 use crate::config;
 use crate::base::{Bar, read_trades};
 use crate::ch2::time_bar::compute_time_bars;
+use crate::ch3::volatility::{Estimator, volatility};
 use std::error::Error;
 use chrono::{Utc, DateTime};
 use plotters::prelude::*;
@@ -8,6 +9,11 @@ use plotters::prelude::*;
 // Returns timestamps where CUSUM filter triggers
 // h: threshold
 pub fn compute_cusum_events(bars: &[Bar], h: f64) -> Vec<(DateTime<Utc>, f64)> {
+    compute_cusum_event_indices(bars, h).into_iter().map(|i| (bars[i].time, bars[i].close)).collect()
+}
+
+// Indices of the bars where CUSUM filter triggers (always >= 1)
+pub fn compute_cusum_event_indices(bars: &[Bar], h: f64) -> Vec<usize> {
     let mut events = Vec::new();
     let mut s_pos = 0.0;
     let mut s_neg = 0.0;
@@ -31,10 +37,10 @@ pub fn compute_cusum_events(bars: &[Bar], h: f64) -> Vec<(DateTime<Utc>, f64)> {
         s_neg = (s_neg + r_t).min(0.0);
 
         if s_pos >= h {
-            events.push((bars[i].time, bars[i].close));
+            events.push(i);
             s_pos = 0.0; // Reset
         } else if s_neg <= -h {
-            events.push((bars[i].time, bars[i].close));
+            events.push(i);
             s_neg = 0.0; // Reset
         }
     }
@@ -54,15 +60,7 @@ pub fn draw_cusum_filter() -> Result<(), Box<dyn Error>> {
     println!("Generated {} time bars.", time_bars.len());
 
     // 2. Calculate Threshold (h) based on Volatility
-    let returns: Vec<f64> = time_bars.windows(2).map(|w| {
-        let prev = w[0].close;
-        let curr = w[1].close;
-        if prev > 0.0 { (curr / prev).ln() } else { 0.0 }
-    }).collect();
-
-    let mean_return = returns.iter().sum::<f64>() / returns.len() as f64;
-    let variance = returns.iter().map(|r| (r - mean_return).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
-    let std_dev = variance.sqrt();
+    let std_dev = volatility(&time_bars, Estimator::CloseToClose);
 
     let threshold = std_dev; // Use 1 std dev as threshold
     println!("Using threshold h = {:.6} (1 std dev of 15m returns)", threshold);
@@ -149,8 +147,8 @@ fn draw_cusum_chart(
     .legend(|(x, y)| Circle::new((x + 10, y), 5, RGBColor(255, 165, 0).filled()));
 
     chart.configure_series_labels()
-        .background_style(&WHITE.mix(0.8))
-        .border_style(&BLACK)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
         .draw()?;
 
     Ok(())
@@ -0,0 +1,176 @@
+use crate::base::stats::{autocorrelation, norm_ppf, pacf, student_t_ppf};
+use crate::ch2::compare_distribution::{compute_stats, standardize};
+use std::error::Error;
+use plotters::prelude::*;
+
+// Return series of one bar type
+pub struct ReturnSeries {
+    pub name: String,
+    pub returns: Vec<f64>,
+}
+
+// Gaussian kernel density at each grid point, Silverman's rule-of-thumb bandwidth
+pub fn kernel_density(data: &[f64], grid: &[f64]) -> Vec<f64> {
+    let n = data.len() as f64;
+    if n < 2.0 {
+        return vec![0.0; grid.len()];
+    }
+    let mean = data.iter().sum::<f64>() / n;
+    let std = (data.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+    let mut sorted = data.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let iqr = sorted[(3 * sorted.len()) / 4] - sorted[sorted.len() / 4];
+    let spread = if iqr > 0.0 { std.min(iqr / 1.34) } else { std };
+    let bandwidth = (0.9 * spread * n.powf(-0.2)).max(1e-12);
+
+    let norm = 1.0 / (n * bandwidth * (2.0 * std::f64::consts::PI).sqrt());
+    grid.iter()
+        .map(|&x| norm * data.iter().map(|&d| (-0.5 * ((x - d) / bandwidth).powi(2)).exp()).sum::<f64>())
+        .collect()
+}
+
+// Student-t degrees of freedom matching the sample excess kurtosis (6 / (df - 4))
+pub fn student_t_df(returns: &[f64]) -> f64 {
+    let kurtosis = compute_stats(returns).kurtosis;
+    if kurtosis > 0.0 { (6.0 / kurtosis + 4.0).clamp(4.1, 100.0) } else { 100.0 }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum QqReference {
+    Normal,
+    StudentT, // df fitted per series, scaled to unit variance
+}
+
+// Overlaid kernel densities of standardized returns with the standard normal
+pub fn draw_kde_chart(series: &[ReturnSeries], output_path: &str) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(output_path, (1024, 768)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let grid: Vec<f64> = (0..=400).map(|i| -5.0 + i as f64 * 0.025).collect();
+    let densities: Vec<Vec<f64>> = series.iter().map(|s| kernel_density(&standardize(&s.returns), &grid)).collect();
+    let normal: Vec<f64> = grid.iter().map(|x| (-0.5 * x * x).exp() / (2.0 * std::f64::consts::PI).sqrt()).collect();
+    let max_y = densities.iter().flatten().chain(&normal).copied().fold(0.0, f64::max);
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Kernel Density of Standardized Returns", ("sans-serif", 30).into_font())
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d(-5.0..5.0, 0.0..max_y * 1.1)?;
+
+    chart.configure_mesh().x_desc("Standard deviations").y_desc("Density").draw()?;
+
+    chart.draw_series(LineSeries::new(grid.iter().copied().zip(normal.iter().copied()), BLACK.stroke_width(2)))?
+        .label("Normal")
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], BLACK));
+
+    for (k, (s, density)) in series.iter().zip(&densities).enumerate() {
+        let color = Palette99::pick(k).to_rgba();
+        chart.draw_series(LineSeries::new(grid.iter().copied().zip(density.iter().copied()), color.stroke_width(2)))?
+            .label(s.name.as_str())
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+
+    chart.configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+
+    Ok(())
+}
+
+// Sample quantiles of standardized returns against reference quantiles
+pub fn draw_qq_chart(series: &[ReturnSeries], reference: QqReference, output_path: &str) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(output_path, (1024, 768)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let title = match reference {
+        QqReference::Normal => "QQ-Plot vs Normal",
+        QqReference::StudentT => "QQ-Plot vs Student-t (fitted df)",
+    };
+    let limit = 6.0;
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption(title, ("sans-serif", 30).into_font())
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d(-limit..limit, -limit..limit)?;
+
+    chart.configure_mesh().x_desc("Theoretical quantiles").y_desc("Sample quantiles").draw()?;
+    chart.draw_series(LineSeries::new(vec![(-limit, -limit), (limit, limit)], &BLACK))?;
+
+    for (k, s) in series.iter().enumerate() {
+        let mut sample = standardize(&s.returns);
+        sample.sort_by(|a, b| a.total_cmp(b));
+        let n = sample.len() as f64;
+        let df = student_t_df(&s.returns);
+        let theoretical = |p: f64| match reference {
+            QqReference::Normal => norm_ppf(p),
+            QqReference::StudentT => student_t_ppf(p, df) * ((df - 2.0) / df).sqrt(),
+        };
+        let points: Vec<(f64, f64)> = sample.iter().enumerate()
+            .map(|(i, &q)| (theoretical((i as f64 + 0.5) / n), q))
+            .filter(|(x, y)| x.abs() <= limit && y.abs() <= limit)
+            .collect();
+
+        let label = match reference {
+            QqReference::Normal => s.name.clone(),
+            QqReference::StudentT => format!("{} (df={:.1})", s.name, df),
+        };
+        let color = Palette99::pick(k).to_rgba();
+        chart.draw_series(points.iter().map(|&p| Circle::new(p, 3, color.filled())))?
+            .label(label)
+            .legend(move |(x, y)| Circle::new((x + 10, y), 3, color.filled()));
+    }
+
+    chart.configure_series_labels()
+        .position(SeriesLabelPosition::UpperLeft)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+
+    Ok(())
+}
+
+// One row per series: ACF (left) and PACF (right) with 95% bands.
+// With `squared` the plots use squared returns, which show volatility clustering.
+pub fn draw_acf_chart(series: &[ReturnSeries], max_lag: usize, squared: bool, output_path: &str) -> Result<(), Box<dyn Error>> {
+    let height = (220 * series.len().max(1)) as u32 + 60;
+    let root = BitMapBackend::new(output_path, (1280, height)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let title = if squared { "ACF / PACF of Squared Returns" } else { "ACF / PACF of Returns" };
+    let root = root.titled(title, ("sans-serif", 30).into_font())?;
+
+    let areas = root.split_evenly((series.len().max(1), 2));
+    for (k, s) in series.iter().enumerate() {
+        let data: Vec<f64> = if squared { s.returns.iter().map(|r| r * r).collect() } else { s.returns.clone() };
+        let lags = max_lag.min(data.len().saturating_sub(1));
+        let band = 1.96 / (data.len().max(1) as f64).sqrt();
+
+        let acf: Vec<f64> = (1..=lags).map(|lag| autocorrelation(&data, lag)).collect();
+        let partial = pacf(&data, lags);
+
+        for (panel, (values, name)) in [(&acf, "ACF"), (&partial, "PACF")].into_iter().enumerate() {
+            let max_abs = values.iter().map(|v| v.abs()).fold(band, f64::max) * 1.3;
+            let mut chart = ChartBuilder::on(&areas[2 * k + panel])
+                .caption(format!("{} {}", s.name, name), ("sans-serif", 18).into_font())
+                .margin(10)
+                .x_label_area_size(25)
+                .y_label_area_size(45)
+                .build_cartesian_2d(0.0..(lags as f64 + 1.0), -max_abs..max_abs)?;
+            chart.configure_mesh().disable_mesh().draw()?;
+
+            chart.draw_series(values.iter().enumerate().map(|(i, &v)| {
+                let x = (i + 1) as f64;
+                Rectangle::new([(x - 0.3, 0.0), (x + 0.3, v)], BLUE.mix(0.7).filled())
+            }))?;
+            for b in [band, -band] {
+                chart.draw_series(LineSeries::new(vec![(0.0, b), (lags as f64 + 1.0, b)], RED.stroke_width(2)))?;
+            }
+            chart.draw_series(LineSeries::new(vec![(0.0, 0.0), (lags as f64 + 1.0, 0.0)], &BLACK))?;
+        }
+    }
+
+    Ok(())
+}
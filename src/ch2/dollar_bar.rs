@@ -1,77 +1,12 @@
 use crate::config;
 use crate::base::{Bar, read_trades, Trade, draw_chart_file};
+use crate::base::fx::converter;
+use crate::base::instrument::registry;
+use crate::ch2::bar_builder::{ClosingRule, compute_information_bars, dollar_metric, unconverted_trades};
 use std::error::Error;
-use chrono::{Utc, TimeZone};
 
 pub fn compute_dollar_bars(trades: &[Trade], interval_dollar: f64) -> Vec<Bar> {
-    if trades.is_empty() {
-        return Vec::new();
-    }
-
-    let mut bars = Vec::new();
-    let first_trade = &trades[0];
-
-    let mut current_dollar_accumulated = 0.0;
-    let mut current_bar_start_idx = 0;
-
-    let mut open = first_trade.price;
-    let mut high = first_trade.price;
-    let mut low = first_trade.price;
-    let mut close = first_trade.price;
-    let mut volume = 0.0;
-    let mut has_data = false;
-
-    for (i, trade) in trades.iter().enumerate() {
-        let dollar_value = trade.price * trade.amount;
-
-        // Update current bar stats
-        if !has_data {
-            open = trade.price;
-            high = trade.price;
-            low = trade.price;
-            close = trade.price;
-            volume = trade.amount;
-            has_data = true;
-            current_bar_start_idx = i;
-        } else {
-            high = high.max(trade.price);
-            low = low.min(trade.price);
-            close = trade.price;
-            volume += trade.amount;
-        }
-
-        current_dollar_accumulated += dollar_value;
-
-        // Check if threshold reached
-        if current_dollar_accumulated >= interval_dollar {
-            bars.push(Bar {
-                time: Utc.timestamp_micros(trades[current_bar_start_idx].timestamp as i64).unwrap(),
-                open,
-                high,
-                low,
-                close,
-                volume,
-            });
-
-            // Reset
-            current_dollar_accumulated = 0.0;
-            has_data = false;
-        }
-    }
-
-    // Optional: Push last partial bar
-    if has_data {
-        bars.push(Bar {
-            time: Utc.timestamp_micros(trades[current_bar_start_idx].timestamp as i64).unwrap(),
-            open,
-            high,
-            low,
-            close,
-            volume,
-        });
-    }
-
-    bars
+    compute_information_bars(trades, dollar_metric, ClosingRule::Fixed(interval_dollar))
 }
 
 pub fn draw_dollar_bar() -> Result<(), Box<dyn Error>> {
@@ -79,9 +14,24 @@ pub fn draw_dollar_bar() -> Result<(), Box<dyn Error>> {
     println!("Reading trades from {}...", file_path);
     let trades = read_trades(file_path)?;
     println!("Read {} trades.", trades.len());
+    if let Some(first) = trades.first() {
+        let instrument = registry().resolve(first);
+        println!(
+            "{} {}: {:?} contract, multiplier {}, notional in {}",
+            instrument.exchange, instrument.symbol, instrument.contract_type,
+            instrument.multiplier, instrument.notional_currency()
+        );
+    }
+    let unconverted = unconverted_trades(&trades);
+    if unconverted > 0 {
+        println!(
+            "Warning: {} of {} trades have no rate into {} at their timestamp and are left out of the dollar value.",
+            unconverted, trades.len(), converter().reference
+        );
+    }
 
     let interval_dollar = 100_000_000.0; // Example dollar threshold (100M USDT)
-    println!("Computing {} dollar value as a bar...", interval_dollar);
+    println!("Computing {} {} value as a bar...", interval_dollar, converter().reference);
     let bars = compute_dollar_bars(&trades, interval_dollar);
     println!("Generated {} bars.", bars.len());
 
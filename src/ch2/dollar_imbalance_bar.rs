@@ -1,101 +1,14 @@
 use crate::config;
 use crate::base::{Bar, read_trades, Trade};
 use crate::ch2::time_bar::compute_time_bars;
+use crate::ch2::bar_builder::{ClosingRule, compute_information_bars, dollar_metric, unconverted_trades};
 use std::error::Error;
-use chrono::{Utc, TimeZone};
 use plotters::prelude::*;
 
 pub fn compute_dollar_imbalance_bars(trades: &[Trade], initial_expected_dollar: f64) -> Vec<Bar> {
-    if trades.is_empty() {
-        return Vec::new();
-    }
-
-    let mut bars = Vec::new();
-    let mut current_imbalance: f64 = 0.0;
-    let mut current_dollar: f64 = 0.0;
-    
-    let mut open = trades[0].price;
-    let mut high = trades[0].price;
-    let mut low = trades[0].price;
-    let mut close;
-    let mut volume = 0.0;
-    
-    let mut prev_price = trades[0].price;
-    let mut prev_tick_rule = 1.0; // Init as buy
-    
-    let mut is_new_bar = true;
-
-    // EWMA parameters
-    let alpha = 0.05; // Smoothing factor
-    let mut ewma_expected_dollar = initial_expected_dollar;
-    let mut ewma_expected_imbalance_per_tick: f64 = 0.5; // Initial guess for |2P[b=1]-1|
-
-    for trade in trades {
-        if is_new_bar {
-            open = trade.price;
-            high = trade.price;
-            low = trade.price;
-            volume = 0.0;
-            is_new_bar = false;
-            current_dollar = 0.0;
-        }
-
-        // 1. Calculate Tick Rule
-        let tick_rule = if trade.price > prev_price {
-            1.0
-        } else if trade.price < prev_price {
-            -1.0
-        } else {
-            prev_tick_rule
-        };
-        prev_tick_rule = tick_rule;
-        prev_price = trade.price;
-
-        // 2. Accumulate Imbalance (Dollar * Tick Rule)
-        let dollar_value = trade.price * trade.amount;
-        let signed_dollar = tick_rule * dollar_value;
-        current_imbalance += signed_dollar;
-        current_dollar += dollar_value;
-
-        // Update Bar stats
-        high = high.max(trade.price);
-        low = low.min(trade.price);
-        close = trade.price;
-        volume += trade.amount;
-
-        // 3. Check Threshold
-        // Threshold = E[D] * |2P[b=1] - 1|
-        let threshold = ewma_expected_dollar * ewma_expected_imbalance_per_tick.abs();
-        
-        if current_imbalance.abs() >= threshold {
-            bars.push(Bar {
-                time: Utc.timestamp_micros(trade.timestamp as i64).unwrap(),
-                open,
-                high,
-                low,
-                close,
-                volume,
-            });
-
-            // Update EWMA
-            ewma_expected_dollar = alpha * current_dollar + (1.0 - alpha) * ewma_expected_dollar;
-            
-            // Update expected imbalance ratio. 
-            let observed_imbalance_ratio = if current_dollar > 0.0 {
-                current_imbalance / current_dollar
-            } else {
-                0.0
-            };
-            
-            ewma_expected_imbalance_per_tick = alpha * observed_imbalance_ratio + (1.0 - alpha) * ewma_expected_imbalance_per_tick;
-
-            // Reset
-            current_imbalance = 0.0;
-            is_new_bar = true;
-        }
-    }
-    
-    bars
+    // Threshold = E[D] * |2P[b=1] - 1|, EWMA alpha 0.05
+    let rule = ClosingRule::Imbalance { initial_expected: initial_expected_dollar, alpha: 0.05 };
+    compute_information_bars(trades, dollar_metric, rule)
 }
 
 pub fn draw_dollar_imbalance_bar() -> Result<(), Box<dyn Error>> {
@@ -103,6 +16,10 @@ pub fn draw_dollar_imbalance_bar() -> Result<(), Box<dyn Error>> {
     println!("Reading trades from {}...", file_path);
     let trades = read_trades(file_path)?;
     println!("Read {} trades.", trades.len());
+    let unconverted = unconverted_trades(&trades);
+    if unconverted > 0 {
+        println!("Warning: {} trades have no rate into the reference currency and are left out of the dollar value.", unconverted);
+    }
 
     // 1. Compute Time Bars (for background context)
     let time_interval_minutes = 15;
@@ -112,7 +29,7 @@ pub fn draw_dollar_imbalance_bar() -> Result<(), Box<dyn Error>> {
 
     // 2. Compute Dollar Imbalance Bars
     // Initial guess: Total Dollar / 300
-    let total_dollar: f64 = trades.iter().map(|t| t.price * t.amount).sum();
+    let total_dollar: f64 = trades.iter().map(dollar_metric).sum();
     let initial_expected_dollar = total_dollar / 300.0;
     
     println!("Computing Dollar Imbalance Bars with dynamic threshold (init D={:.2})...", initial_expected_dollar);
@@ -198,8 +115,8 @@ fn draw_overlay_chart(
     .legend(|(x, y)| Circle::new((x + 10, y), 4, BLUE.filled()));
 
     chart.configure_series_labels()
-        .background_style(&WHITE.mix(0.8))
-        .border_style(&BLACK)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
         .draw()?;
 
     Ok(())
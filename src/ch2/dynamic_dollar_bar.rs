@@ -0,0 +1,169 @@
+use crate::config;
+use crate::base::{Bar, read_trades, Trade, draw_chart_file};
+use crate::ch2::bar_builder::{BarAccumulator, dollar_metric, unconverted_trades};
+use std::error::Error;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use plotters::prelude::*;
+
+#[derive(Debug, Clone)]
+pub struct DollarThreshold {
+    pub time: DateTime<Utc>, // first trade of the bar the threshold applies to
+    pub threshold: f64,
+}
+
+// Dollar bars whose threshold is reset at the start of every bar to
+// trailing dollar volume / (covered days * target bars per day).
+// Only trades strictly before the bar's first trade are used; the trailing window is
+// min(window, elapsed time), and the first bar uses `initial_threshold`.
+pub fn compute_dynamic_dollar_bars(
+    trades: &[Trade],
+    window: Duration,
+    bars_per_day: f64,
+    initial_threshold: f64,
+) -> (Vec<Bar>, Vec<DollarThreshold>) {
+    let mut bars = Vec::new();
+    let mut thresholds = Vec::new();
+    if trades.is_empty() {
+        return (bars, thresholds);
+    }
+
+    let window_us = window.num_microseconds().unwrap_or(i64::MAX) as u64;
+    let first_timestamp = trades[0].timestamp;
+    let mut acc = BarAccumulator::new();
+    let mut accumulated = 0.0;
+    let mut threshold = initial_threshold;
+
+    // Trailing window is trades[window_start..i]
+    let mut window_start = 0;
+    let mut window_dollar = 0.0;
+
+    for (i, trade) in trades.iter().enumerate() {
+        if acc.is_empty() {
+            while window_start < i && trades[window_start].timestamp + window_us < trade.timestamp {
+                window_dollar -= dollar_metric(&trades[window_start]);
+                window_start += 1;
+            }
+            let covered_us = (trade.timestamp - first_timestamp).min(window_us);
+            if covered_us > 0 && window_dollar > 0.0 {
+                let covered_days = covered_us as f64 / 86_400_000_000.0;
+                threshold = window_dollar / (covered_days * bars_per_day);
+            }
+            thresholds.push(DollarThreshold {
+                time: Utc.timestamp_micros(trade.timestamp as i64).unwrap(),
+                threshold,
+            });
+        }
+
+        acc.update(trade);
+        let dollar = dollar_metric(trade);
+        accumulated += dollar;
+        window_dollar += dollar;
+
+        if accumulated >= threshold {
+            bars.push(acc.finish(acc.start_timestamp));
+            accumulated = 0.0;
+        }
+    }
+
+    // Push last partial bar
+    if !acc.is_empty() {
+        bars.push(acc.finish(acc.start_timestamp));
+    }
+
+    (bars, thresholds)
+}
+
+// Bars and the threshold each one was built with, one row per bar
+pub fn write_dynamic_dollar_csv(bars: &[Bar], thresholds: &[DollarThreshold], path: &str) -> Result<(), Box<dyn Error>> {
+    let mut wtr = csv::Writer::from_path(path)?;
+    wtr.write_record(["time", "open", "high", "low", "close", "volume", "threshold"])?;
+    for (bar, t) in bars.iter().zip(thresholds) {
+        wtr.write_record([
+            bar.time.to_rfc3339(),
+            bar.open.to_string(),
+            bar.high.to_string(),
+            bar.low.to_string(),
+            bar.close.to_string(),
+            bar.volume.to_string(),
+            t.threshold.to_string(),
+        ])?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+pub fn draw_dynamic_dollar_bar() -> Result<(), Box<dyn Error>> {
+    let file_path = config::TARDIS_CSV_PATH;
+    println!("Reading trades from {}...", file_path);
+    let trades = read_trades(file_path)?;
+    println!("Read {} trades.", trades.len());
+    let unconverted = unconverted_trades(&trades);
+    if unconverted > 0 {
+        println!("Warning: {} trades have no rate into the reference currency and are left out of the dollar value.", unconverted);
+    }
+
+    let window = Duration::hours(1);
+    let bars_per_day = 96.0; // as many as 15 minute time bars
+    let initial_threshold = 10_000_000.0;
+    println!(
+        "Computing dynamic dollar bars (trailing {} min window, {} bars per day)...",
+        window.num_minutes(), bars_per_day
+    );
+    let (bars, thresholds) = compute_dynamic_dollar_bars(&trades, window, bars_per_day, initial_threshold);
+    println!("Generated {} bars.", bars.len());
+
+    let min_t = thresholds.iter().map(|t| t.threshold).fold(f64::INFINITY, f64::min);
+    let max_t = thresholds.iter().map(|t| t.threshold).fold(f64::NEG_INFINITY, f64::max);
+    println!("Threshold range: {:.2} - {:.2}", min_t, max_t);
+
+    std::fs::create_dir_all("src/ch2/result")?;
+    let csv_path = "src/ch2/result/dynamic_dollar_bars.csv";
+    write_dynamic_dollar_csv(&bars, &thresholds, csv_path)?;
+    println!("Bars and thresholds saved to {}", csv_path);
+
+    let threshold_path = "src/ch2/result/dynamic_dollar_thresholds.png";
+    draw_threshold_chart(&thresholds, threshold_path)?;
+
+    let output_path = "src/ch2/result/dynamic_dollar_bars.png";
+    println!("Drawing chart to {}...", output_path);
+    draw_chart_file("Dynamic Dollar Bar (BTCUSDT)", &bars, output_path)?;
+
+    // Try to open the file automatically
+    #[cfg(target_os = "windows")]
+    std::process::Command::new("cmd")
+        .args(["/C", "start", output_path])
+        .spawn()?;
+
+    #[cfg(target_os = "macos")]
+    std::process::Command::new("open")
+        .arg(output_path)
+        .spawn()?;
+
+    #[cfg(target_os = "linux")]
+    std::process::Command::new("xdg-open")
+        .arg(output_path)
+        .spawn()?;
+
+    Ok(())
+}
+
+fn draw_threshold_chart(thresholds: &[DollarThreshold], output_path: &str) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(output_path, (1024, 600)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let min_time = thresholds.first().ok_or("No thresholds")?.time;
+    let max_time = thresholds.last().unwrap().time;
+    let max_t = thresholds.iter().map(|t| t.threshold).fold(0.0, f64::max);
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Dynamic Dollar Threshold", ("sans-serif", 30).into_font())
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(100)
+        .build_cartesian_2d(min_time..max_time, 0.0..max_t * 1.1)?;
+
+    chart.configure_mesh().y_desc("Dollar per bar").draw()?;
+    chart.draw_series(LineSeries::new(thresholds.iter().map(|t| (t.time, t.threshold)), &BLUE))?;
+
+    Ok(())
+}
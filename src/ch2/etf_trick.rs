@@ -0,0 +1,233 @@
+use crate::config;
+use crate::base::{Bar, read_trades, Trade, draw_chart_file};
+use crate::ch2::time_bar::compute_time_bars;
+use crate::ch2::tick_bar::compute_tick_bars;
+use crate::ch2::cusum_filter::compute_cusum_events;
+use crate::ch2::futures_roll::synthetic_contracts;
+use crate::ch10::bet_sizing::return_std;
+use std::collections::HashSet;
+use std::error::Error;
+use chrono::{DateTime, Utc};
+
+// One instrument of the basket; every vector is aligned with `bars`
+#[derive(Debug, Clone)]
+pub struct BasketLeg {
+    pub name: String,
+    pub bars: Vec<Bar>,
+    pub carry: Vec<f64>, // dividend / funding per unit held, paid over each bar (price units, + received)
+    pub fx: Vec<f64>,    // rate converting the leg's price currency into the basket currency
+    pub cost_rate: f64,  // proportional transaction cost on traded notional
+}
+
+#[derive(Debug, Clone)]
+pub struct EtfPoint {
+    pub time: DateTime<Utc>,
+    pub value: f64,         // K_t, basket value in the basket currency
+    pub holdings: Vec<f64>, // h_{i,t}, units held over the next bar
+    pub cost: f64,          // transaction cost paid at this bar
+    pub rebalanced: bool,
+}
+
+// Keep only bar times present in every series, so the legs share one index
+pub fn align_bars(series: &[Vec<Bar>]) -> Vec<Vec<Bar>> {
+    let Some(first) = series.first() else {
+        return Vec::new();
+    };
+    let common: HashSet<DateTime<Utc>> = first.iter()
+        .map(|b| b.time)
+        .filter(|t| series[1..].iter().all(|s| s.iter().any(|b| b.time == *t)))
+        .collect();
+    series.iter()
+        .map(|s| s.iter().filter(|b| common.contains(&b.time)).cloned().collect())
+        .collect()
+}
+
+// ETF trick (AFML 2.4.1): the value of a self-financing basket that holds weights[t][i]
+// (any sign, scaled to gross exposure 1) and rebalances at bars where rebalance[t] is true.
+//   K_t = K_{t-1} + sum_i h_{i,t-1} fx_{i,t} (p_{i,t} - o_{i,t} + d_{i,t}) - costs_t
+// where o_{i,t} is the open of bar t after a rebalance at t-1 (trades execute at the next open)
+// and the previous close otherwise, and
+//   h_{i,t} = w_{i,t} K_t / (o_{i,t+1} fx_{i,t} sum_j |w_{j,t}|).
+// Rebalancing costs cost_rate * |h_{i,t} - h_{i,t-1}| * o_{i,t+1} * fx_{i,t} are charged at t+1.
+pub fn etf_trick(legs: &[BasketLeg], weights: &[Vec<f64>], rebalance: &[bool], initial_value: f64) -> Vec<EtfPoint> {
+    let n = legs.iter().map(|l| l.bars.len()).min().unwrap_or(0).min(weights.len());
+    let mut points: Vec<EtfPoint> = Vec::with_capacity(n);
+    let mut holdings = vec![0.0; legs.len()];
+    let mut value = initial_value;
+    let mut rebalanced_last = true;
+    let mut pending_cost = 0.0;
+
+    for (t, w) in weights.iter().enumerate().take(n) {
+        if t > 0 {
+            let gain: f64 = legs.iter().zip(&holdings)
+                .map(|(leg, h)| {
+                    let bar = &leg.bars[t];
+                    let entry = if rebalanced_last { bar.open } else { leg.bars[t - 1].close };
+                    h * leg.fx[t] * (bar.close - entry + leg.carry[t])
+                })
+                .sum();
+            value += gain - pending_cost;
+        }
+
+        let do_rebalance = t == 0 || rebalance.get(t).copied().unwrap_or(false);
+        let mut cost = 0.0;
+        if do_rebalance {
+            let gross: f64 = w.iter().map(|x| x.abs()).sum();
+            for (i, leg) in legs.iter().enumerate() {
+                // execution at the next open; the last bar has none, so use its close
+                let execution = if t + 1 < n { leg.bars[t + 1].open } else { leg.bars[t].close };
+                let target = if gross > 0.0 && execution > 0.0 {
+                    w[i] * value / (execution * leg.fx[t] * gross)
+                } else {
+                    0.0
+                };
+                cost += leg.cost_rate * (target - holdings[i]).abs() * execution * leg.fx[t];
+                holdings[i] = target;
+            }
+        }
+        pending_cost = cost;
+        rebalanced_last = do_rebalance;
+
+        points.push(EtfPoint {
+            time: legs[0].bars[t].time,
+            value,
+            holdings: holdings.clone(),
+            cost,
+            rebalanced: do_rebalance,
+        });
+    }
+    points
+}
+
+// The basket as bars: open = previous value, close = value; volume is the weighted dollar
+// volume of the legs in basket units
+pub fn etf_bars(legs: &[BasketLeg], weights: &[Vec<f64>], points: &[EtfPoint]) -> Vec<Bar> {
+    points.iter().enumerate()
+        .map(|(t, p)| {
+            let open = if t > 0 { points[t - 1].value } else { p.value };
+            let gross: f64 = weights[t].iter().map(|w| w.abs()).sum::<f64>().max(f64::MIN_POSITIVE);
+            let dollar_volume: f64 = legs.iter().enumerate()
+                .map(|(i, leg)| weights[t][i].abs() / gross * leg.bars[t].volume * leg.bars[t].close * leg.fx[t])
+                .sum();
+            Bar {
+                time: p.time,
+                open,
+                high: open.max(p.value),
+                low: open.min(p.value),
+                close: p.value,
+                volume: if p.value > 0.0 { dollar_volume / p.value } else { 0.0 },
+            }
+        })
+        .collect()
+}
+
+// One synthetic trade per basket bar, so the trade-based bar builders can consume the basket
+pub fn etf_trades(symbol: &str, bars: &[Bar]) -> Vec<Trade> {
+    bars.iter().enumerate()
+        .map(|(i, bar)| Trade {
+            exchange: "etf_trick".to_string(),
+            symbol: symbol.to_string(),
+            timestamp: bar.time.timestamp_micros() as u64,
+            local_timestamp: bar.time.timestamp_micros() as u64,
+            id: i as u64,
+            side: if bar.close >= bar.open { "buy" } else { "sell" }.to_string(),
+            price: bar.close,
+            amount: bar.volume,
+        })
+        .collect()
+}
+
+pub fn run_etf_trick() -> Result<(), Box<dyn Error>> {
+    let file_path = config::TARDIS_CSV_PATH;
+    println!("Reading trades from {}...", file_path);
+    let trades = read_trades(file_path)?;
+    println!("Read {} trades.", trades.len());
+
+    // 1. Basis spread: long the perpetual, short a dated contract expiring at the end of the data
+    let interval_minutes = 5;
+    let future = synthetic_contracts(&trades, 1).pop().ok_or("No trades")?;
+    let aligned = align_bars(&[
+        compute_time_bars(&trades, interval_minutes),
+        compute_time_bars(&future.trades, interval_minutes),
+    ]);
+    let n = aligned[0].len();
+    println!("Aligned {} {} minute bars of {} and {}.", n, interval_minutes, trades[0].symbol, future.symbol);
+
+    // Perpetual longs pay 0.01% funding every 8 hours, accrued per bar
+    let funding_rate = 0.0001 * interval_minutes as f64 / 480.0;
+    let legs = vec![
+        BasketLeg {
+            name: trades[0].symbol.clone(),
+            carry: aligned[0].iter().map(|b| -funding_rate * b.close).collect(),
+            bars: aligned[0].clone(),
+            fx: vec![1.0; n],
+            cost_rate: 0.0004,
+        },
+        BasketLeg {
+            name: future.symbol.clone(),
+            carry: vec![0.0; n],
+            bars: aligned[1].clone(),
+            fx: vec![1.0; n],
+            cost_rate: 0.0002,
+        },
+    ];
+    let weights = vec![vec![1.0, -1.0]; n];
+    let rebalance_every = 12; // hourly
+    let rebalance: Vec<bool> = (0..n).map(|t| t % rebalance_every == 0).collect();
+
+    // 2. ETF trick
+    let initial_value = 1.0;
+    let points = etf_trick(&legs, &weights, &rebalance, initial_value);
+    let last = points.last().ok_or("No basket values")?;
+    let total_cost: f64 = points.iter().map(|p| p.cost).sum();
+    println!(
+        "Basket value {:.6} -> {:.6} after {} rebalances (costs {:.6}).",
+        initial_value, last.value, points.iter().filter(|p| p.rebalanced).count(), total_cost
+    );
+
+    std::fs::create_dir_all("src/ch2/result")?;
+    let csv_path = "src/ch2/result/etf_trick.csv";
+    let mut wtr = csv::Writer::from_path(csv_path)?;
+    let mut header = vec!["time".to_string(), "value".to_string(), "cost".to_string(), "rebalanced".to_string()];
+    header.extend(legs.iter().map(|l| format!("holdings_{}", l.name)));
+    wtr.write_record(&header)?;
+    for p in &points {
+        let mut row = vec![p.time.to_rfc3339(), p.value.to_string(), p.cost.to_string(), p.rebalanced.to_string()];
+        row.extend(p.holdings.iter().map(|h| h.to_string()));
+        wtr.write_record(&row)?;
+    }
+    wtr.flush()?;
+    println!("Basket values saved to {}", csv_path);
+
+    // 3. The basket as a single instrument
+    let bars = etf_bars(&legs, &weights, &points);
+    let h = return_std(&bars);
+    let events = compute_cusum_events(&bars, h);
+    println!("CUSUM filter on the basket (h = {:.6}): {} events.", h, events.len());
+
+    let basket_trades = etf_trades("PERP-FUT", &bars);
+    let tick_bars = compute_tick_bars(&basket_trades, rebalance_every);
+    println!("Re-sampled the basket into {} tick bars of {} basket prints.", tick_bars.len(), rebalance_every);
+
+    let output_path = "src/ch2/result/etf_trick.png";
+    println!("Drawing chart to {}...", output_path);
+    draw_chart_file("ETF Trick (perpetual - future spread)", &tick_bars, output_path)?;
+
+    // Try to open the file automatically
+    #[cfg(target_os = "windows")]
+    std::process::Command::new("cmd")
+        .args(["/C", "start", output_path])
+        .spawn()?;
+
+    #[cfg(target_os = "macos")]
+    std::process::Command::new("open")
+        .arg(output_path)
+        .spawn()?;
+
+    #[cfg(target_os = "linux")]
+    std::process::Command::new("xdg-open")
+        .arg(output_path)
+        .spawn()?;
+
+    Ok(())
+}
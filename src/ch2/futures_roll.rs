@@ -0,0 +1,388 @@
+use crate::config;
+use crate::base::{read_trades, Trade, draw_chart_file};
+use crate::base::instrument::registry;
+use crate::ch2::bar_builder::{dollar_metric, volume_metric};
+use crate::ch2::dollar_bar::compute_dollar_bars;
+use std::error::Error;
+use std::path::Path;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use plotters::prelude::*;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OpenInterest {
+    pub timestamp: u64, // microseconds
+    pub open_interest: f64,
+}
+
+// One dated futures contract; trades and open interest sorted by timestamp
+#[derive(Debug, Clone)]
+pub struct Contract {
+    pub symbol: String,
+    pub expiry: u64, // microseconds
+    pub trades: Vec<Trade>,
+    pub open_interest: Vec<OpenInterest>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum RollRule {
+    VolumeCrossover(Duration), // first window in which the next contract trades more volume
+    OpenInterestCrossover,     // first observation at which the next contract has more open interest
+    BeforeExpiry(Duration),    // fixed time before the front contract expires
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Adjustment {
+    None,
+    Backward, // latest contract unadjusted, history shifted by the later roll gaps
+    Forward,  // first contract unadjusted, later contracts shifted by the earlier roll gaps
+}
+
+#[derive(Debug, Clone)]
+pub struct RollEvent {
+    pub timestamp: u64,
+    pub from: usize, // index into the contracts
+    pub to: usize,
+    pub gap: f64, // next contract price - front contract price at the roll
+}
+
+// Trades of the active contract, with the adjusted price of each
+#[derive(Debug, Clone)]
+pub struct ContinuousSeries {
+    pub trades: Vec<Trade>, // raw contract trades
+    pub contract: Vec<usize>,
+    pub adjusted: Vec<f64>,
+    pub rolls: Vec<RollEvent>,
+}
+
+// Open interest CSV with at least `timestamp` and `open_interest` columns (e.g. Tardis derivative_ticker)
+pub fn read_open_interest<P: AsRef<Path>>(path: P) -> Result<Vec<OpenInterest>, Box<dyn Error>> {
+    let mut rdr = csv::Reader::from_path(path)?;
+    let mut rows = Vec::new();
+    for result in rdr.deserialize() {
+        let row: OpenInterest = result?;
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+// Last trade price at or before timestamp, or the first trade after it
+fn price_at(trades: &[Trade], timestamp: u64) -> Option<f64> {
+    let i = trades.partition_point(|t| t.timestamp <= timestamp);
+    if i > 0 { Some(trades[i - 1].price) } else { trades.first().map(|t| t.price) }
+}
+
+fn open_interest_at(rows: &[OpenInterest], timestamp: u64) -> f64 {
+    let i = rows.partition_point(|r| r.timestamp <= timestamp);
+    if i > 0 { rows[i - 1].open_interest } else { 0.0 }
+}
+
+fn volume_between(trades: &[Trade], start: u64, end: u64) -> f64 {
+    let lo = trades.partition_point(|t| t.timestamp < start);
+    let hi = trades.partition_point(|t| t.timestamp < end);
+    trades[lo..hi].iter().map(volume_metric).sum()
+}
+
+// Time at which the front contract is rolled into the next one, never after the front expiry
+fn roll_time(front: &Contract, next: &Contract, rule: RollRule, not_before: u64) -> u64 {
+    let candidate = match rule {
+        RollRule::BeforeExpiry(before) => {
+            Some(front.expiry.saturating_sub(before.num_microseconds().unwrap_or(0).max(0) as u64))
+        }
+        RollRule::VolumeCrossover(window) => {
+            let step = window.num_microseconds().unwrap_or(1).max(1) as u64;
+            let first = front.trades.first().map_or(not_before, |t| t.timestamp).max(not_before);
+            (0..)
+                .map(|k| first + k * step)
+                .take_while(|start| *start < front.expiry)
+                .find(|&start| {
+                    let end = start + step;
+                    volume_between(&next.trades, start, end) > volume_between(&front.trades, start, end)
+                })
+                .map(|start| (start + step).min(front.expiry))
+        }
+        RollRule::OpenInterestCrossover => next.open_interest.iter()
+            .map(|r| r.timestamp)
+            .filter(|&t| t >= not_before && t < front.expiry)
+            .find(|&t| open_interest_at(&next.open_interest, t) > open_interest_at(&front.open_interest, t)),
+    };
+    candidate.unwrap_or(front.expiry).clamp(not_before, front.expiry)
+}
+
+// Roll from each contract into the next, contracts sorted by expiry
+pub fn roll_schedule(contracts: &[Contract], rule: RollRule) -> Vec<RollEvent> {
+    let mut rolls = Vec::new();
+    let mut not_before = 0;
+    for (k, pair) in contracts.windows(2).enumerate() {
+        let timestamp = roll_time(&pair[0], &pair[1], rule, not_before);
+        let gap = match (price_at(&pair[1].trades, timestamp), price_at(&pair[0].trades, timestamp)) {
+            (Some(next), Some(front)) => next - front,
+            _ => 0.0,
+        };
+        rolls.push(RollEvent { timestamp, from: k, to: k + 1, gap });
+        not_before = timestamp;
+    }
+    rolls
+}
+
+// Stitch the active contract between rolls and remove the roll gaps
+pub fn build_continuous(contracts: &[Contract], rolls: &[RollEvent], adjustment: Adjustment) -> ContinuousSeries {
+    let total_gap: f64 = rolls.iter().map(|r| r.gap).sum();
+    let mut series = ContinuousSeries { trades: Vec::new(), contract: Vec::new(), adjusted: Vec::new(), rolls: rolls.to_vec() };
+
+    let mut gap_before = 0.0; // sum of the gaps of rolls into contracts up to k
+    for (k, contract) in contracts.iter().enumerate() {
+        if k > 0 {
+            gap_before += rolls[k - 1].gap;
+        }
+        let start = if k > 0 { rolls[k - 1].timestamp } else { 0 };
+        let end = rolls.get(k).map_or(u64::MAX, |r| r.timestamp);
+        let shift = match adjustment {
+            Adjustment::None => 0.0,
+            Adjustment::Backward => total_gap - gap_before,
+            Adjustment::Forward => -gap_before,
+        };
+        for trade in contract.trades.iter().filter(|t| t.timestamp >= start && t.timestamp < end) {
+            series.trades.push(trade.clone());
+            series.contract.push(k);
+            series.adjusted.push(trade.price + shift);
+        }
+    }
+    series
+}
+
+impl ContinuousSeries {
+    // Copies of the trades carrying the given prices, for the bar builders
+    pub fn with_prices(&self, prices: &[f64]) -> Vec<Trade> {
+        self.trades.iter().zip(prices)
+            .map(|(t, &price)| Trade { price, ..t.clone() })
+            .collect()
+    }
+
+    // Non-negative series compounding the adjusted price changes over the raw price
+    // (AFML snippet 2.3), started at the first raw price
+    pub fn non_negative_prices(&self) -> Vec<f64> {
+        let Some(first) = self.trades.first() else {
+            return Vec::new();
+        };
+        let mut prices = Vec::with_capacity(self.trades.len());
+        let mut level = first.price;
+        prices.push(level);
+        for i in 1..self.trades.len() {
+            let base = self.trades[i - 1].price;
+            if base > 0.0 {
+                level *= 1.0 + (self.adjusted[i] - self.adjusted[i - 1]) / base;
+            }
+            prices.push(level);
+        }
+        prices
+    }
+}
+
+// Three contracts carved out of one perpetual trade file: each trades at a contango premium
+// proportional to its time to expiry, and liquidity moves to the next contract as expiry nears
+pub fn synthetic_contracts(trades: &[Trade], num_contracts: usize) -> Vec<Contract> {
+    let (Some(first), Some(last)) = (trades.first(), trades.last()) else {
+        return Vec::new();
+    };
+    let hour = 3_600_000_000.0;
+    let life = (last.timestamp - first.timestamp) / num_contracts.max(1) as u64;
+    let mut contracts: Vec<Contract> = (0..num_contracts)
+        .map(|k| Contract {
+            symbol: format!("{}-F{}", first.symbol, k + 1),
+            expiry: first.timestamp + (k as u64 + 1) * life + 1,
+            trades: Vec::new(),
+            open_interest: Vec::new(),
+        })
+        .collect();
+
+    let instrument = registry().resolve(first);
+    let premium_per_hour = 0.0005;
+    let oi_step = 300_000_000; // one open interest observation every 5 minutes
+    let mut next_oi = first.timestamp;
+    for trade in trades {
+        let Some(front) = contracts.iter().position(|c| c.expiry > trade.timestamp) else {
+            continue;
+        };
+        let hours_to_expiry = (contracts[front].expiry - trade.timestamp) as f64 / hour;
+        let share = (hours_to_expiry / 6.0).clamp(0.1, 0.9);
+        let record_oi = trade.timestamp >= next_oi;
+        if record_oi {
+            next_oi += oi_step;
+        }
+
+        for (k, weight) in [(front, share), (front + 1, 1.0 - share)] {
+            let Some(contract) = contracts.get_mut(k) else {
+                continue;
+            };
+            let hours = (contract.expiry - trade.timestamp) as f64 / hour;
+            contract.trades.push(Trade {
+                symbol: contract.symbol.clone(),
+                price: instrument.round_price(trade.price * (1.0 + premium_per_hour * hours)),
+                amount: trade.amount * weight,
+                ..trade.clone()
+            });
+            if record_oi {
+                let oi_share = if k == front { (hours_to_expiry / 5.0).clamp(0.05, 0.95) } else { 1.0 - (hours_to_expiry / 5.0).clamp(0.05, 0.95) };
+                contract.open_interest.push(OpenInterest { timestamp: trade.timestamp, open_interest: 10_000.0 * oi_share });
+            }
+        }
+    }
+    contracts
+}
+
+fn load_contracts() -> Result<Vec<Contract>, Box<dyn Error>> {
+    let mut contracts = Vec::new();
+    for (symbol, expiry, trades_path, oi_path) in config::FUTURES_CONTRACTS {
+        println!("Reading {} trades from {}...", symbol, trades_path);
+        let mut trades = read_trades(trades_path)?;
+        trades.sort_by_key(|t| t.timestamp);
+        let open_interest = if oi_path.is_empty() { Vec::new() } else { read_open_interest(oi_path)? };
+        let expiry = DateTime::parse_from_rfc3339(expiry)?.with_timezone(&Utc);
+        contracts.push(Contract {
+            symbol: symbol.to_string(),
+            expiry: expiry.timestamp_micros() as u64,
+            trades,
+            open_interest,
+        });
+    }
+    contracts.sort_by_key(|c| c.expiry);
+    Ok(contracts)
+}
+
+fn to_datetime(timestamp: u64) -> DateTime<Utc> {
+    Utc.timestamp_micros(timestamp as i64).unwrap()
+}
+
+pub fn run_futures_roll() -> Result<(), Box<dyn Error>> {
+    // 1. Contracts from config, or synthetic contracts from the perpetual trades
+    let mut contracts = load_contracts()?;
+    if contracts.is_empty() {
+        let file_path = config::TARDIS_CSV_PATH;
+        println!("No futures contracts configured; deriving 3 synthetic contracts from {}...", file_path);
+        let trades = read_trades(file_path)?;
+        contracts = synthetic_contracts(&trades, 3);
+    }
+    if contracts.len() < 2 {
+        return Err("At least two contracts are needed to roll".into());
+    }
+    for c in &contracts {
+        println!("{}: {} trades, expiry {}", c.symbol, c.trades.len(), to_datetime(c.expiry));
+    }
+
+    // 2. Roll schedules
+    let rules = [
+        ("volume crossover (30 min)", RollRule::VolumeCrossover(Duration::minutes(30))),
+        ("open interest crossover", RollRule::OpenInterestCrossover),
+        ("2 hours before expiry", RollRule::BeforeExpiry(Duration::hours(2))),
+    ];
+    for (name, rule) in &rules {
+        println!("--- Rolls: {} ---", name);
+        for roll in roll_schedule(&contracts, *rule) {
+            println!(
+                "{} -> {} at {}, gap {:.2}",
+                contracts[roll.from].symbol, contracts[roll.to].symbol, to_datetime(roll.timestamp), roll.gap
+            );
+        }
+    }
+
+    // 3. Continuous series on the volume crossover rule
+    let rolls = roll_schedule(&contracts, rules[0].1);
+    let raw = build_continuous(&contracts, &rolls, Adjustment::None);
+    let backward = build_continuous(&contracts, &rolls, Adjustment::Backward);
+    let forward = build_continuous(&contracts, &rolls, Adjustment::Forward);
+    let non_negative = backward.non_negative_prices();
+    println!("Continuous series: {} trades.", backward.trades.len());
+
+    std::fs::create_dir_all("src/ch2/result")?;
+    let csv_path = "src/ch2/result/continuous_futures.csv";
+    let mut wtr = csv::Writer::from_path(csv_path)?;
+    wtr.write_record(["timestamp", "contract", "raw", "backward", "forward", "non_negative", "amount"])?;
+    for i in 0..backward.trades.len() {
+        wtr.write_record([
+            backward.trades[i].timestamp.to_string(),
+            contracts[backward.contract[i]].symbol.clone(),
+            raw.adjusted[i].to_string(),
+            backward.adjusted[i].to_string(),
+            forward.adjusted[i].to_string(),
+            non_negative[i].to_string(),
+            backward.trades[i].amount.to_string(),
+        ])?;
+    }
+    wtr.flush()?;
+    println!("Continuous series saved to {}", csv_path);
+
+    // 4. Dollar bars on the non-negative series
+    let trades = backward.with_prices(&non_negative);
+    let total_dollar: f64 = trades.iter().map(dollar_metric).sum();
+    let bars = compute_dollar_bars(&trades, total_dollar / 96.0);
+    println!("Generated {} dollar bars from the non-negative series.", bars.len());
+    draw_chart_file("Dollar Bar (continuous futures)", &bars, "src/ch2/result/continuous_futures_bars.png")?;
+
+    let output_path = "src/ch2/result/continuous_futures.png";
+    println!("Drawing chart to {}...", output_path);
+    draw_continuous_chart(&raw, &[("backward", &backward.adjusted), ("forward", &forward.adjusted), ("non-negative", &non_negative)], output_path)?;
+
+    // Try to open the file automatically
+    #[cfg(target_os = "windows")]
+    std::process::Command::new("cmd")
+        .args(["/C", "start", output_path])
+        .spawn()?;
+
+    #[cfg(target_os = "macos")]
+    std::process::Command::new("open")
+        .arg(output_path)
+        .spawn()?;
+
+    #[cfg(target_os = "linux")]
+    std::process::Command::new("xdg-open")
+        .arg(output_path)
+        .spawn()?;
+
+    Ok(())
+}
+
+fn draw_continuous_chart(raw: &ContinuousSeries, adjusted: &[(&str, &Vec<f64>)], output_path: &str) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(output_path, (1024, 768)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let n = raw.trades.len();
+    let step = (n / 2000).max(1);
+    let times: Vec<DateTime<Utc>> = raw.trades.iter().step_by(step).map(|t| to_datetime(t.timestamp)).collect();
+    let min_time = *times.first().ok_or("No trades")?;
+    let max_time = *times.last().unwrap();
+    let all = raw.adjusted.iter().chain(adjusted.iter().flat_map(|(_, p)| p.iter()));
+    let min_p = all.clone().copied().fold(f64::INFINITY, f64::min);
+    let max_p = all.copied().fold(f64::NEG_INFINITY, f64::max);
+    let pad = (max_p - min_p) * 0.05;
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Continuous Futures", ("sans-serif", 30).into_font())
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(70)
+        .build_cartesian_2d(min_time..max_time, (min_p - pad)..(max_p + pad))?;
+
+    chart.configure_mesh().y_desc("Price").draw()?;
+
+    let lines = std::iter::once(("raw", &raw.adjusted)).chain(adjusted.iter().map(|(name, p)| (*name, *p)));
+    for (k, (name, prices)) in lines.enumerate() {
+        let color = Palette99::pick(k).to_rgba();
+        let points: Vec<(DateTime<Utc>, f64)> = times.iter().copied().zip(prices.iter().step_by(step).copied()).collect();
+        chart.draw_series(LineSeries::new(points, color.stroke_width(2)))?
+            .label(name)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+
+    for roll in &raw.rolls {
+        let t = to_datetime(roll.timestamp);
+        chart.draw_series(LineSeries::new(vec![(t, min_p - pad), (t, max_p + pad)], BLACK.mix(0.4)))?;
+    }
+
+    chart.configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+
+    Ok(())
+}
@@ -1,19 +1,45 @@
+pub mod bar_builder;
 pub mod time_bar;
 pub mod tick_bar;
 pub mod volume_bar;
 pub mod dollar_bar;
+pub mod dynamic_dollar_bar;
 pub mod tick_imbalance_bar;
 pub mod volume_imbalance_bar;
 pub mod dollar_imbalance_bar;
+pub mod range_bar;
+pub mod renko_bar;
+pub mod volatility_bar;
 pub mod cusum_filter;
+pub mod futures_roll;
+pub mod etf_trick;
+pub mod pca_weights;
+pub mod book_features;
+pub mod trade_classification;
+pub mod calibration;
+pub mod bootstrap;
 pub mod compare_distribution;
+pub mod distribution_charts;
+pub mod bar_quality;
 
+pub use bar_builder::draw_run_bar;
 pub use time_bar::draw_time_bar;
 pub use tick_bar::draw_tick_bar;
 pub use volume_bar::draw_volume_bar;
 pub use dollar_bar::draw_dollar_bar;
+pub use dynamic_dollar_bar::draw_dynamic_dollar_bar;
 pub use tick_imbalance_bar::draw_tick_imbalance_bar;
 pub use volume_imbalance_bar::draw_volume_imbalance_bar;
 pub use dollar_imbalance_bar::draw_dollar_imbalance_bar;
+pub use range_bar::draw_range_bar;
+pub use renko_bar::draw_renko_bar;
+pub use volatility_bar::draw_volatility_bar;
 pub use cusum_filter::draw_cusum_filter;
-pub use compare_distribution::run_compare_distribution;
\ No newline at end of file
+pub use futures_roll::run_futures_roll;
+pub use etf_trick::run_etf_trick;
+pub use pca_weights::run_pca_weights;
+pub use book_features::{run_book_features, run_book_features_demo};
+pub use trade_classification::run_trade_classification;
+pub use calibration::run_calibration;
+pub use compare_distribution::run_compare_distribution;
+pub use bar_quality::run_bar_quality;
\ No newline at end of file
@@ -0,0 +1,223 @@
+use crate::config;
+use crate::base::{Bar, read_trades, draw_chart_file};
+use crate::base::linalg::{covariance_matrix, symmetric_eigen};
+use crate::ch2::time_bar::compute_time_bars;
+use crate::ch2::etf_trick::{BasketLeg, align_bars, etf_bars, etf_trick};
+use crate::ch2::futures_roll::synthetic_contracts;
+use crate::ch13::synthetic_data::{OuParams, generate_ou_trades};
+use std::error::Error;
+
+#[derive(Debug, Clone)]
+pub struct PcaAllocation {
+    pub index: usize,         // first bar the weights apply to
+    pub weights: Vec<f64>,    // notional weight of each instrument
+    pub risk_share: Vec<f64>, // share of the basket variance on each principal component
+}
+
+// Eigenvalues below this fraction of the largest one are treated as zero. Sampled returns of
+// collinear instruments keep some price-rounding noise, so their smallest eigenvalue sits
+// around 1e-8 of the largest rather than at machine precision.
+pub const EIGEN_TOLERANCE: f64 = 1e-6;
+
+// PCA weights (AFML snippet 2.1): the allocation whose variance on principal component k is
+// risk_target^2 * risk_dist[k]. Components are sorted by descending eigenvalue; without a
+// risk distribution all risk goes to the last (lowest variance) component, a hedge.
+// Numerically zero components (collinear instruments) would need unbounded weights: they get
+// no risk, the default hedge uses the last non-zero component instead, and risk assigned to
+// them explicitly is dropped with a warning.
+pub fn pca_weights(cov: &[Vec<f64>], risk_dist: Option<&[f64]>, risk_target: f64) -> Result<Vec<f64>, Box<dyn Error>> {
+    let (values, vectors) = symmetric_eigen(cov);
+    let m = values.len();
+    let tolerance = EIGEN_TOLERANCE * values.first().copied().unwrap_or(0.0).max(0.0);
+    let degenerate = |k: usize| values[k] <= tolerance;
+    let dist: Vec<f64> = match risk_dist {
+        Some(d) if d.len() != m => {
+            return Err(format!("Risk distribution has {} entries for {} components", d.len(), m).into());
+        }
+        Some(d) => d.to_vec(),
+        None => {
+            let last = (0..m).rev().find(|&k| !degenerate(k));
+            (0..m).map(|k| if Some(k) == last { 1.0 } else { 0.0 }).collect()
+        }
+    };
+    for k in (0..m).filter(|&k| degenerate(k) && dist[k] > 0.0) {
+        eprintln!(
+            "Warning: component {} has a numerically zero eigenvalue ({:.3e}); its risk share {} is dropped",
+            k + 1, values[k], dist[k]
+        );
+    }
+    let loads: Vec<f64> = (0..m)
+        .map(|k| if degenerate(k) { 0.0 } else { risk_target * (dist[k] / values[k]).sqrt() })
+        .collect();
+    Ok((0..m).map(|i| (0..m).map(|k| vectors[i][k] * loads[k]).sum()).collect())
+}
+
+// Share of w' cov w carried by each principal component
+pub fn component_risk_share(cov: &[Vec<f64>], weights: &[f64]) -> Vec<f64> {
+    let (values, vectors) = symmetric_eigen(cov);
+    let m = values.len();
+    let contributions: Vec<f64> = (0..m)
+        .map(|k| values[k] * (0..m).map(|i| vectors[i][k] * weights[i]).sum::<f64>().powi(2))
+        .collect();
+    let total: f64 = contributions.iter().sum();
+    contributions.iter().map(|c| if total > 0.0 { c / total } else { 0.0 }).collect()
+}
+
+// Log returns of aligned bar series, one row per bar from the second one on
+pub fn aligned_returns(series: &[Vec<Bar>]) -> Vec<Vec<f64>> {
+    let n = series.iter().map(|s| s.len()).min().unwrap_or(0);
+    (1..n)
+        .map(|t| series.iter()
+            .map(|s| if s[t - 1].close > 0.0 && s[t].close > 0.0 { (s[t].close / s[t - 1].close).ln() } else { 0.0 })
+            .collect())
+        .collect()
+}
+
+// Weights re-estimated every `step` bars from the covariance of the previous `window` returns.
+// returns[r] is the return into bar r + 1, so an estimate at bar t only uses returns up to bar t.
+// Eigenvectors are defined up to sign, so each estimate is flipped to point the same way as
+// the previous one; otherwise the basket would reverse at random rebalances.
+pub fn rolling_pca_weights(
+    returns: &[Vec<f64>],
+    window: usize,
+    step: usize,
+    risk_dist: Option<&[f64]>,
+    risk_target: f64,
+) -> Result<Vec<PcaAllocation>, Box<dyn Error>> {
+    let mut allocations: Vec<PcaAllocation> = Vec::new();
+    for t in (window..=returns.len()).step_by(step.max(1)) {
+        let cov = covariance_matrix(&returns[t - window..t]);
+        let mut weights = pca_weights(&cov, risk_dist, risk_target)?;
+        if let Some(prev) = allocations.last()
+            && prev.weights.iter().zip(&weights).map(|(a, b)| a * b).sum::<f64>() < 0.0 {
+            weights.iter_mut().for_each(|w| *w = -*w);
+        }
+        let risk_share = component_risk_share(&cov, &weights);
+        allocations.push(PcaAllocation { index: t, weights, risk_share });
+    }
+    Ok(allocations)
+}
+
+// Per-bar weights and rebalance flags for the ETF trick; flat until the first estimate
+pub fn allocation_schedule(allocations: &[PcaAllocation], num_bars: usize, num_instruments: usize) -> (Vec<Vec<f64>>, Vec<bool>) {
+    let mut weights = vec![vec![0.0; num_instruments]; num_bars];
+    let mut rebalance = vec![false; num_bars];
+    let mut current = vec![0.0; num_instruments];
+    let mut next = allocations.iter().peekable();
+    for (t, w) in weights.iter_mut().enumerate() {
+        while let Some(a) = next.next_if(|a| a.index <= t) {
+            current = a.weights.clone();
+            rebalance[t] = true;
+        }
+        *w = current.clone();
+    }
+    (weights, rebalance)
+}
+
+pub fn run_pca_weights() -> Result<(), Box<dyn Error>> {
+    let file_path = config::TARDIS_CSV_PATH;
+    println!("Reading trades from {}...", file_path);
+    let trades = read_trades(file_path)?;
+    println!("Read {} trades.", trades.len());
+
+    // 1. Three instruments: the perpetual, a dated contract and an independent OU asset
+    //    traded at the same timestamps
+    let future = synthetic_contracts(&trades, 1).pop().ok_or("No trades")?;
+    let ou = OuParams { forecast: 3400.0, half_life: 50_000.0, sigma: 0.5, initial_price: 3400.0 };
+    let mut other = generate_ou_trades(&ou, trades.len(), 0.01, 7);
+    for (o, t) in other.iter_mut().zip(&trades) {
+        o.timestamp = t.timestamp;
+        o.local_timestamp = t.local_timestamp;
+    }
+
+    let interval_minutes = 5;
+    let names = [trades[0].symbol.clone(), future.symbol.clone(), "OU".to_string()];
+    let aligned = align_bars(&[
+        compute_time_bars(&trades, interval_minutes),
+        compute_time_bars(&future.trades, interval_minutes),
+        compute_time_bars(&other, interval_minutes),
+    ]);
+    let n = aligned[0].len();
+    println!("Aligned {} {} minute bars of {}.", n, interval_minutes, names.join(", "));
+
+    // 2. Full-sample weights for a hedge and for equal risk on every component
+    let returns = aligned_returns(&aligned);
+    let cov = covariance_matrix(&returns);
+    let (values, _) = symmetric_eigen(&cov);
+    println!("Eigenvalues: {:?}", values);
+    let zero = values.iter().filter(|v| **v <= EIGEN_TOLERANCE * values[0]).count();
+    if zero > 0 {
+        println!("Warning: {} numerically zero component(s); the instruments are (nearly) collinear.", zero);
+    }
+    let equal = vec![1.0 / names.len() as f64; names.len()];
+    for (label, dist) in [("hedge (lowest non-zero component)", None), ("equal risk", Some(equal.as_slice()))] {
+        let w = pca_weights(&cov, dist, 0.01)?;
+        println!("{}: weights {:?}, risk share {:?}", label, w, component_risk_share(&cov, &w));
+    }
+
+    // 3. Rolling hedge weights (2 hour window, re-estimated hourly) and the basket they produce
+    let window = 24;
+    let step = 12;
+    let allocations = rolling_pca_weights(&returns, window, step, None, 0.01)?;
+    println!("Re-estimated weights {} times.", allocations.len());
+
+    std::fs::create_dir_all("src/ch2/result")?;
+    let weights_path = "src/ch2/result/pca_weights.csv";
+    let mut wtr = csv::Writer::from_path(weights_path)?;
+    let mut header = vec!["time".to_string()];
+    header.extend(names.iter().map(|s| format!("weight_{}", s)));
+    header.extend((1..=names.len()).map(|k| format!("risk_pc{}", k)));
+    wtr.write_record(&header)?;
+    for a in &allocations {
+        let mut row = vec![aligned[0][a.index].time.to_rfc3339()];
+        row.extend(a.weights.iter().map(|w| w.to_string()));
+        row.extend(a.risk_share.iter().map(|r| r.to_string()));
+        wtr.write_record(&row)?;
+    }
+    wtr.flush()?;
+    println!("Weights saved to {}", weights_path);
+
+    let legs: Vec<BasketLeg> = names.iter().zip(&aligned)
+        .map(|(name, bars)| BasketLeg {
+            name: name.clone(),
+            bars: bars.clone(),
+            carry: vec![0.0; n],
+            fx: vec![1.0; n],
+            cost_rate: 0.0,
+        })
+        .collect();
+    let (weights, rebalance) = allocation_schedule(&allocations, n, names.len());
+    let points = etf_trick(&legs, &weights, &rebalance, 1.0);
+    let bars = etf_bars(&legs, &weights, &points);
+
+    let basket_path = "src/ch2/result/pca_basket.csv";
+    let mut wtr = csv::Writer::from_path(basket_path)?;
+    wtr.write_record(["time", "value"])?;
+    for p in &points {
+        wtr.write_record([p.time.to_rfc3339(), p.value.to_string()])?;
+    }
+    wtr.flush()?;
+    println!("Basket series saved to {}", basket_path);
+
+    let output_path = "src/ch2/result/pca_basket.png";
+    println!("Drawing chart to {}...", output_path);
+    draw_chart_file("PCA Hedge Basket", &bars, output_path)?;
+
+    // Try to open the file automatically
+    #[cfg(target_os = "windows")]
+    std::process::Command::new("cmd")
+        .args(["/C", "start", output_path])
+        .spawn()?;
+
+    #[cfg(target_os = "macos")]
+    std::process::Command::new("open")
+        .arg(output_path)
+        .spawn()?;
+
+    #[cfg(target_os = "linux")]
+    std::process::Command::new("xdg-open")
+        .arg(output_path)
+        .spawn()?;
+
+    Ok(())
+}
@@ -0,0 +1,109 @@
+use crate::config;
+use crate::base::{Bar, read_trades, Trade, draw_chart_file};
+use crate::ch2::bar_builder::BarAccumulator;
+use crate::ch2::time_bar::compute_time_bars;
+use std::collections::VecDeque;
+use std::error::Error;
+
+#[derive(Debug, Clone, Copy)]
+pub enum RangeThreshold {
+    // Close when high - low reaches a fixed price amount
+    Fixed(f64),
+    // Close when high - low reaches multiplier * average true range of the last `window` range bars,
+    // starting from `initial` until enough bars exist
+    Atr { window: usize, multiplier: f64, initial: f64 },
+}
+
+pub fn compute_range_bars(trades: &[Trade], threshold: RangeThreshold) -> Vec<Bar> {
+    if trades.is_empty() {
+        return Vec::new();
+    }
+
+    let mut bars: Vec<Bar> = Vec::new();
+    let mut true_ranges: VecDeque<f64> = VecDeque::new();
+    let mut range = match threshold {
+        RangeThreshold::Fixed(r) => r,
+        RangeThreshold::Atr { initial, .. } => initial,
+    };
+
+    let mut acc = BarAccumulator::new();
+    for trade in trades {
+        acc.update(trade);
+
+        if acc.high - acc.low >= range {
+            // True range uses the previous bar close to include gaps
+            let prev_close = bars.last().map(|b| b.close).unwrap_or(acc.open);
+            let true_range = acc.high.max(prev_close) - acc.low.min(prev_close);
+            bars.push(acc.finish(acc.start_timestamp));
+
+            if let RangeThreshold::Atr { window, multiplier, .. } = threshold {
+                true_ranges.push_back(true_range);
+                if true_ranges.len() > window {
+                    true_ranges.pop_front();
+                }
+                if true_ranges.len() == window {
+                    range = multiplier * true_ranges.iter().sum::<f64>() / window as f64;
+                }
+            }
+        }
+    }
+
+    // Push last partial bar
+    if !acc.is_empty() {
+        bars.push(acc.finish(acc.start_timestamp));
+    }
+
+    bars
+}
+
+// Average high-low range of 15 minute time bars, a range comparable to the clock bars
+pub fn average_time_bar_range(trades: &[Trade]) -> f64 {
+    let time_bars = compute_time_bars(trades, 15);
+    if time_bars.is_empty() {
+        return 0.0;
+    }
+    time_bars.iter().map(|b| b.high - b.low).sum::<f64>() / time_bars.len() as f64
+}
+
+pub fn draw_range_bar() -> Result<(), Box<dyn Error>> {
+    let file_path = config::TARDIS_CSV_PATH;
+    println!("Reading trades from {}...", file_path);
+    let trades = read_trades(file_path)?;
+    println!("Read {} trades.", trades.len());
+
+    let range = average_time_bar_range(&trades);
+    println!("Computing range bars with a fixed range of {:.2}...", range);
+    let fixed_bars = compute_range_bars(&trades, RangeThreshold::Fixed(range));
+    println!("Generated {} fixed range bars.", fixed_bars.len());
+
+    let threshold = RangeThreshold::Atr { window: 20, multiplier: 1.0, initial: range };
+    println!("Computing range bars scaled by a 20 bar ATR...");
+    let bars = compute_range_bars(&trades, threshold);
+    println!("Generated {} ATR range bars.", bars.len());
+
+    std::fs::create_dir_all("src/ch2/result")?;
+    let fixed_path = "src/ch2/result/range_bars.png";
+    draw_chart_file("Range Bar (BTCUSDT)", &fixed_bars, fixed_path)?;
+
+    let output_path = "src/ch2/result/atr_range_bars.png";
+    println!("Drawing chart to {}...", output_path);
+    draw_chart_file("ATR Range Bar (BTCUSDT)", &bars, output_path)?;
+
+    // Try to open the file automatically
+    #[cfg(target_os = "windows")]
+    std::process::Command::new("cmd")
+        .args(["/C", "start", output_path])
+        .spawn()?;
+
+    #[cfg(target_os = "macos")]
+    std::process::Command::new("open")
+        .arg(output_path)
+        .spawn()?;
+
+    #[cfg(target_os = "linux")]
+    std::process::Command::new("xdg-open")
+        .arg(output_path)
+        .spawn()?;
+
+    Ok(())
+}
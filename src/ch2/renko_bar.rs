@@ -0,0 +1,106 @@
+use crate::config;
+use crate::base::{Bar, read_trades, Trade, draw_chart_file};
+use crate::ch2::bar_builder::volume_metric;
+use crate::ch2::time_bar::compute_time_bars;
+use std::error::Error;
+use chrono::{Utc, TimeZone};
+
+// Renko bricks of a fixed size. A brick in the current direction needs a move of one
+// brick from the last close; a reversal needs two (past the open of the last brick).
+// A single trade can complete several bricks; extra bricks carry no volume.
+pub fn compute_renko_bars(trades: &[Trade], brick_size: f64) -> Vec<Bar> {
+    if trades.is_empty() || brick_size <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut bars = Vec::new();
+    let mut last_close = trades[0].price;
+    let mut direction = 0.0; // +1 up, -1 down, 0 before the first brick
+    let mut current_bar_start_idx = 0;
+    let mut volume = 0.0;
+
+    for (i, trade) in trades.iter().enumerate() {
+        volume += volume_metric(trade);
+
+        loop {
+            let up = trade.price >= last_close + if direction < 0.0 { 2.0 } else { 1.0 } * brick_size;
+            let down = trade.price <= last_close - if direction > 0.0 { 2.0 } else { 1.0 } * brick_size;
+            if !up && !down {
+                break;
+            }
+
+            let new_direction = if up { 1.0 } else { -1.0 };
+            // On reversal the new brick starts from the previous brick's open
+            let open = if direction != 0.0 && new_direction != direction {
+                last_close - direction * brick_size
+            } else {
+                last_close
+            };
+            let close = open + new_direction * brick_size;
+
+            bars.push(Bar {
+                time: Utc.timestamp_micros(trades[current_bar_start_idx].timestamp as i64).unwrap(),
+                open,
+                high: open.max(close),
+                low: open.min(close),
+                close,
+                volume,
+            });
+
+            last_close = close;
+            direction = new_direction;
+            volume = 0.0;
+            current_bar_start_idx = i;
+        }
+
+        if volume == 0.0 {
+            current_bar_start_idx = (i + 1).min(trades.len() - 1);
+        }
+    }
+
+    bars
+}
+
+// Average absolute close-to-close move of 15 minute time bars
+pub fn average_time_bar_move(trades: &[Trade]) -> f64 {
+    let time_bars = compute_time_bars(trades, 15);
+    if time_bars.len() < 2 {
+        return 0.0;
+    }
+    time_bars.windows(2).map(|w| (w[1].close - w[0].close).abs()).sum::<f64>() / (time_bars.len() - 1) as f64
+}
+
+pub fn draw_renko_bar() -> Result<(), Box<dyn Error>> {
+    let file_path = config::TARDIS_CSV_PATH;
+    println!("Reading trades from {}...", file_path);
+    let trades = read_trades(file_path)?;
+    println!("Read {} trades.", trades.len());
+
+    let brick_size = average_time_bar_move(&trades);
+    println!("Computing renko bricks of size {:.2}...", brick_size);
+    let bars = compute_renko_bars(&trades, brick_size);
+    println!("Generated {} bricks.", bars.len());
+
+    std::fs::create_dir_all("src/ch2/result")?;
+    let output_path = "src/ch2/result/renko_bars.png";
+    println!("Drawing chart to {}...", output_path);
+    draw_chart_file("Renko Bar (BTCUSDT)", &bars, output_path)?;
+
+    // Try to open the file automatically
+    #[cfg(target_os = "windows")]
+    std::process::Command::new("cmd")
+        .args(["/C", "start", output_path])
+        .spawn()?;
+
+    #[cfg(target_os = "macos")]
+    std::process::Command::new("open")
+        .arg(output_path)
+        .spawn()?;
+
+    #[cfg(target_os = "linux")]
+    std::process::Command::new("xdg-open")
+        .arg(output_path)
+        .spawn()?;
+
+    Ok(())
+}
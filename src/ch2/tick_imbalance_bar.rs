@@ -1,93 +1,14 @@
 use crate::config;
-use crate::base::{Bar, read_trades, Trade, draw_chart_file};
+use crate::base::{Bar, read_trades, Trade};
 use crate::ch2::time_bar::compute_time_bars;
+use crate::ch2::bar_builder::{ClosingRule, compute_information_bars, tick_metric};
 use std::error::Error;
-use chrono::{Utc, TimeZone};
 use plotters::prelude::*;
 
 pub fn compute_tick_imbalance_bars(trades: &[Trade], initial_expected_ticks: f64) -> Vec<Bar> {
-    if trades.is_empty() {
-        return Vec::new();
-    }
-
-    let mut bars = Vec::new();
-    let mut current_imbalance: f64 = 0.0;
-    let mut current_ticks: f64 = 0.0;
-    
-    let mut open = trades[0].price;
-    let mut high = trades[0].price;
-    let mut low = trades[0].price;
-    let mut close;
-    let mut volume = 0.0;
-    
-    let mut prev_price = trades[0].price;
-    let mut prev_tick_rule = 1.0; // Init as buy
-    
-    let mut is_new_bar = true;
-
-    // EWMA parameters
-    let alpha = 0.05; // Smoothing factor (approx 20 bars)
-    let mut ewma_expected_ticks = initial_expected_ticks;
-    let mut ewma_expected_imbalance_per_tick: f64 = 0.5; // Initial guess for |2P[b=1]-1|
-
-    for trade in trades {
-        if is_new_bar {
-            open = trade.price;
-            high = trade.price;
-            low = trade.price;
-            volume = 0.0;
-            is_new_bar = false;
-            current_ticks = 0.0;
-        }
-
-        // 1. Calculate Tick Rule
-        let tick_rule = if trade.price > prev_price {
-            1.0
-        } else if trade.price < prev_price {
-            -1.0
-        } else {
-            prev_tick_rule
-        };
-        prev_tick_rule = tick_rule;
-        prev_price = trade.price;
-
-        // 2. Accumulate Imbalance
-        current_imbalance += tick_rule;
-        current_ticks += 1.0;
-
-        // Update Bar stats
-        high = high.max(trade.price);
-        low = low.min(trade.price);
-        close = trade.price;
-        volume += trade.amount;
-
-        // 3. Check Threshold
-        // Threshold = E[T] * |2P[b=1] - 1|
-        let threshold = ewma_expected_ticks * ewma_expected_imbalance_per_tick.abs();
-        
-        if current_imbalance.abs() >= threshold {
-            bars.push(Bar {
-                time: Utc.timestamp_micros(trade.timestamp as i64).unwrap(), // Use current trade time (end of bar)
-                open,
-                high,
-                low,
-                close,
-                volume,
-            });
-
-            // Update EWMA
-            ewma_expected_ticks = alpha * current_ticks + (1.0 - alpha) * ewma_expected_ticks;
-            
-            let current_imbalance_per_tick = current_imbalance / current_ticks;
-            ewma_expected_imbalance_per_tick = alpha * current_imbalance_per_tick + (1.0 - alpha) * ewma_expected_imbalance_per_tick;
-
-            // Reset
-            current_imbalance = 0.0;
-            is_new_bar = true;
-        }
-    }
-    
-    bars
+    // Threshold = E[T] * |2P[b=1] - 1|, EWMA alpha 0.05 (approx 20 bars)
+    let rule = ClosingRule::Imbalance { initial_expected: initial_expected_ticks, alpha: 0.05 };
+    compute_information_bars(trades, tick_metric, rule)
 }
 
 pub fn draw_tick_imbalance_bar() -> Result<(), Box<dyn Error>> {
@@ -190,8 +111,8 @@ fn draw_overlay_chart(
     .legend(|(x, y)| Circle::new((x + 10, y), 4, BLUE.filled()));
 
     chart.configure_series_labels()
-        .background_style(&WHITE.mix(0.8))
-        .border_style(&BLACK)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
         .draw()?;
 
     Ok(())
@@ -1,5 +1,6 @@
 use crate::config;
 use crate::base::{Bar, read_trades, Trade, draw_chart_file};
+use crate::ch2::bar_builder::volume_metric;
 use std::error::Error;
 use chrono::{Utc, TimeZone};
 
@@ -50,14 +51,14 @@ pub fn compute_time_bars(trades: &[Trade], interval_minutes: i64) -> Vec<Bar> {
             high = trade.price;
             low = trade.price;
             close = trade.price;
-            volume = trade.amount;
+            volume = volume_metric(trade);
             has_data = true;
         } else {
             // Update current bar
             high = high.max(trade.price);
             low = low.min(trade.price);
             close = trade.price;
-            volume += trade.amount;
+            volume += volume_metric(trade);
             has_data = true;
         }
     }
@@ -0,0 +1,266 @@
+use crate::config;
+use crate::base::{read_trades, Trade};
+use crate::base::book::{BookSnapshot, Quote, read_quotes};
+use crate::ch2::bar_builder::TickRule;
+use crate::ch2::book_features::load_snapshots;
+use std::error::Error;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy)]
+pub struct TopOfBook {
+    pub timestamp: u64, // microseconds
+    pub bid: f64,
+    pub ask: f64,
+}
+
+impl TopOfBook {
+    pub fn from_quote(q: &Quote) -> Self {
+        TopOfBook { timestamp: q.timestamp, bid: q.bid_price, ask: q.ask_price }
+    }
+
+    pub fn from_snapshot(s: &BookSnapshot) -> Option<Self> {
+        Some(TopOfBook { timestamp: s.timestamp, bid: s.best_bid()?.price, ask: s.best_ask()?.price })
+    }
+
+    pub fn mid(&self) -> f64 {
+        0.5 * (self.bid + self.ask)
+    }
+
+    // Locked or crossed quotes carry no information about the aggressor
+    pub fn is_valid(&self) -> bool {
+        self.bid > 0.0 && self.ask > self.bid
+    }
+}
+
+// As-of join: the last quote strictly before trade.timestamp - delay, so a quote updated by
+// the trade itself (same timestamp) is not used. Quotes must be sorted by timestamp.
+pub fn quotes_asof(trades: &[Trade], quotes: &[TopOfBook], delay: u64) -> Vec<Option<TopOfBook>> {
+    trades.iter()
+        .map(|t| {
+            let cutoff = t.timestamp.saturating_sub(delay);
+            let i = quotes.partition_point(|q| q.timestamp < cutoff);
+            if i > 0 { Some(quotes[i - 1]) } else { None }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Classifier {
+    TickRule,
+    // Lee and Ready (1991): above the mid is a buy, below a sell, at the mid the tick rule
+    LeeReady,
+    // Ellis, Michaely and O'Hara (2000): at the ask a buy, at the bid a sell, otherwise the tick rule
+    Emo,
+    // Chakrabarty, Li, Nguyen and Van Ness (2007): within the top 30% of the spread a buy,
+    // within the bottom 30% a sell, in the middle 40% or outside the quotes the tick rule
+    Clnv,
+}
+
+impl Classifier {
+    pub const ALL: [Classifier; 4] = [Classifier::TickRule, Classifier::LeeReady, Classifier::Emo, Classifier::Clnv];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Classifier::TickRule => "Tick rule",
+            Classifier::LeeReady => "Lee-Ready",
+            Classifier::Emo => "EMO",
+            Classifier::Clnv => "CLNV",
+        }
+    }
+
+    // Sign from the quote, or None to fall back to the tick rule
+    fn quote_sign(&self, price: f64, quote: &TopOfBook) -> Option<f64> {
+        let spread = quote.ask - quote.bid;
+        let eps = 1e-9 * quote.ask;
+        match self {
+            Classifier::TickRule => None,
+            Classifier::LeeReady => {
+                let mid = quote.mid();
+                if price > mid + eps { Some(1.0) } else if price < mid - eps { Some(-1.0) } else { None }
+            }
+            Classifier::Emo => {
+                if (price - quote.ask).abs() <= eps { Some(1.0) }
+                else if (price - quote.bid).abs() <= eps { Some(-1.0) }
+                else { None }
+            }
+            Classifier::Clnv => {
+                if price <= quote.ask + eps && price >= quote.ask - 0.3 * spread - eps { Some(1.0) }
+                else if price >= quote.bid - eps && price <= quote.bid + 0.3 * spread + eps { Some(-1.0) }
+                else { None }
+            }
+        }
+    }
+}
+
+// +1 buy / -1 sell per trade, and whether the quote (rather than the tick rule) decided it
+pub fn classify(trades: &[Trade], quotes: &[Option<TopOfBook>], classifier: Classifier) -> Vec<(f64, bool)> {
+    let Some(first) = trades.first() else { return Vec::new() };
+    let mut tick = TickRule::new(first.price);
+    trades.iter().zip(quotes)
+        .map(|(trade, quote)| {
+            let tick_sign = tick.sign(trade.price);
+            match quote.filter(|q| q.is_valid()).and_then(|q| classifier.quote_sign(trade.price, &q)) {
+                Some(sign) => (sign, true),
+                None => (tick_sign, false),
+            }
+        })
+        .collect()
+}
+
+// Where the trade printed relative to the prevailing quote
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TradeLocation {
+    AtQuote,
+    Inside,
+    AtMid,
+    Outside,
+    NoQuote,
+}
+
+impl TradeLocation {
+    pub const ALL: [TradeLocation; 5] =
+        [TradeLocation::AtQuote, TradeLocation::Inside, TradeLocation::AtMid, TradeLocation::Outside, TradeLocation::NoQuote];
+
+    pub fn of(price: f64, quote: Option<&TopOfBook>) -> Self {
+        let Some(q) = quote.filter(|q| q.is_valid()) else { return TradeLocation::NoQuote };
+        let eps = 1e-9 * q.ask;
+        if (price - q.ask).abs() <= eps || (price - q.bid).abs() <= eps {
+            TradeLocation::AtQuote
+        } else if (price - q.mid()).abs() <= eps {
+            TradeLocation::AtMid
+        } else if price > q.bid && price < q.ask {
+            TradeLocation::Inside
+        } else {
+            TradeLocation::Outside
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TradeLocation::AtQuote => "at quote",
+            TradeLocation::Inside => "inside spread",
+            TradeLocation::AtMid => "at mid",
+            TradeLocation::Outside => "outside quotes",
+            TradeLocation::NoQuote => "no quote",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClassificationReport {
+    pub classifier: Classifier,
+    pub trades: usize,             // trades with a reported side
+    pub accuracy: f64,             // share agreeing with the reported side
+    pub buy_accuracy: f64,         // among reported buys
+    pub sell_accuracy: f64,        // among reported sells
+    pub quote_share: f64,          // share signed from the quote rather than the tick rule
+    pub by_location: Vec<(TradeLocation, usize, f64)>, // (location, trades, accuracy)
+}
+
+// Agreement with the exchange-reported aggressor side; trades whose side is neither
+// "buy" nor "sell" (e.g. Tardis "unknown") are left out
+pub fn diagnostics(trades: &[Trade], quotes: &[Option<TopOfBook>], classifier: Classifier) -> ClassificationReport {
+    let signs = classify(trades, quotes, classifier);
+    let mut n = 0;
+    let (mut correct, mut from_quote) = (0, 0);
+    let (mut buys, mut buys_correct, mut sells, mut sells_correct) = (0, 0, 0, 0);
+    let mut locations = [(0usize, 0usize); 5];
+    for ((trade, quote), (sign, quoted)) in trades.iter().zip(quotes).zip(&signs) {
+        let reported = match trade.side.as_str() {
+            "buy" => 1.0,
+            "sell" => -1.0,
+            _ => continue,
+        };
+        let hit = *sign == reported;
+        n += 1;
+        correct += hit as usize;
+        from_quote += *quoted as usize;
+        if reported > 0.0 {
+            buys += 1;
+            buys_correct += hit as usize;
+        } else {
+            sells += 1;
+            sells_correct += hit as usize;
+        }
+        let location = TradeLocation::of(trade.price, quote.as_ref());
+        let slot = &mut locations[TradeLocation::ALL.iter().position(|l| *l == location).unwrap()];
+        slot.0 += 1;
+        slot.1 += hit as usize;
+    }
+    let ratio = |a: usize, b: usize| if b > 0 { a as f64 / b as f64 } else { f64::NAN };
+    ClassificationReport {
+        classifier,
+        trades: n,
+        accuracy: ratio(correct, n),
+        buy_accuracy: ratio(buys_correct, buys),
+        sell_accuracy: ratio(sells_correct, sells),
+        quote_share: ratio(from_quote, n),
+        by_location: TradeLocation::ALL.iter().zip(locations)
+            .map(|(l, (count, hits))| (*l, count, ratio(hits, count)))
+            .collect(),
+    }
+}
+
+pub fn run_trade_classification() -> Result<(), Box<dyn Error>> {
+    let file_path = config::TARDIS_CSV_PATH;
+    println!("Reading trades from {}...", file_path);
+    let trades = read_trades(file_path)?;
+    println!("Read {} trades.", trades.len());
+
+    // 1. Top of book: quotes when available, otherwise the best levels of recorded book
+    //    snapshots or of the L2 book after every update batch, so no quote is staler than the
+    //    feed. Quote rules are only meaningful against real quotes, so there is no synthetic
+    //    fallback.
+    let mut quotes: Vec<TopOfBook> = if Path::new(config::TARDIS_QUOTES_PATH).exists() {
+        println!("Reading quotes from {}...", config::TARDIS_QUOTES_PATH);
+        read_quotes(config::TARDIS_QUOTES_PATH)?.iter().map(TopOfBook::from_quote).collect()
+    } else {
+        load_snapshots(0, 1)
+            .map_err(|e| format!("Trade classification needs quotes or book snapshots. {}", e))?
+            .iter()
+            .filter_map(TopOfBook::from_snapshot)
+            .collect()
+    };
+    if quotes.is_empty() {
+        return Err("No valid quotes to classify trades against".into());
+    }
+    quotes.sort_by_key(|q| q.timestamp);
+    println!("{} quotes.", quotes.len());
+    let aligned = quotes_asof(&trades, &quotes, 0);
+
+    // 2. Agreement of each classifier with the reported side
+    let reports: Vec<ClassificationReport> = Classifier::ALL.iter().map(|c| diagnostics(&trades, &aligned, *c)).collect();
+    println!("\n{:<10} {:>9} {:>9} {:>9} {:>9} {:>12}", "Classifier", "Trades", "Accuracy", "Buys", "Sells", "From quote");
+    for r in &reports {
+        println!("{:<10} {:>9} {:>9.4} {:>9.4} {:>9.4} {:>12.4}",
+            r.classifier.name(), r.trades, r.accuracy, r.buy_accuracy, r.sell_accuracy, r.quote_share);
+    }
+    println!("\nAccuracy by trade location:");
+    print!("{:<16} {:>9}", "Location", "Trades");
+    for r in &reports {
+        print!(" {:>10}", r.classifier.name());
+    }
+    println!();
+    for (i, location) in TradeLocation::ALL.iter().enumerate() {
+        print!("{:<16} {:>9}", location.name(), reports[0].by_location[i].1);
+        for r in &reports {
+            print!(" {:>10.4}", r.by_location[i].2);
+        }
+        println!();
+    }
+
+    std::fs::create_dir_all("src/ch2/result")?;
+    let csv_path = "src/ch2/result/trade_classification.csv";
+    let mut wtr = csv::Writer::from_path(csv_path)?;
+    wtr.write_record(["classifier", "location", "trades", "accuracy"])?;
+    for r in &reports {
+        wtr.write_record([r.classifier.name(), "all", &r.trades.to_string(), &r.accuracy.to_string()])?;
+        for (location, count, accuracy) in &r.by_location {
+            wtr.write_record([r.classifier.name(), location.name(), &count.to_string(), &accuracy.to_string()])?;
+        }
+    }
+    wtr.flush()?;
+    println!("\nClassification report saved to {}", csv_path);
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use crate::config;
+use crate::base::{Bar, read_trades, Trade, draw_chart_file};
+use crate::ch2::time_bar::compute_time_bars;
+use crate::ch2::bar_builder::{ClosingRule, compute_information_bars};
+use std::error::Error;
+
+// Close a bar when the realized variance (sum of squared trade-to-trade log returns)
+// inside it reaches `variance_threshold`
+pub fn compute_volatility_bars(trades: &[Trade], variance_threshold: f64) -> Vec<Bar> {
+    let mut prev_price = trades.first().map(|t| t.price).unwrap_or(0.0);
+    let squared_return = move |trade: &Trade| {
+        let r = if prev_price > 0.0 && trade.price > 0.0 { (trade.price / prev_price).ln() } else { 0.0 };
+        prev_price = trade.price;
+        r * r
+    };
+    compute_information_bars(trades, squared_return, ClosingRule::Fixed(variance_threshold))
+}
+
+// Realized variance threshold giving about as many bars as 15 minute time bars
+pub fn average_time_bar_variance(trades: &[Trade]) -> f64 {
+    let num_time_bars = compute_time_bars(trades, 15).len().max(1);
+    let total: f64 = trades.windows(2)
+        .filter(|w| w[0].price > 0.0 && w[1].price > 0.0)
+        .map(|w| (w[1].price / w[0].price).ln().powi(2))
+        .sum();
+    total / num_time_bars as f64
+}
+
+pub fn draw_volatility_bar() -> Result<(), Box<dyn Error>> {
+    let file_path = config::TARDIS_CSV_PATH;
+    println!("Reading trades from {}...", file_path);
+    let trades = read_trades(file_path)?;
+    println!("Read {} trades.", trades.len());
+
+    let threshold = average_time_bar_variance(&trades);
+    println!("Computing volatility bars with a realized variance of {:.3e}...", threshold);
+    let bars = compute_volatility_bars(&trades, threshold);
+    println!("Generated {} bars.", bars.len());
+
+    std::fs::create_dir_all("src/ch2/result")?;
+    let output_path = "src/ch2/result/volatility_bars.png";
+    println!("Drawing chart to {}...", output_path);
+    draw_chart_file("Volatility Bar (BTCUSDT)", &bars, output_path)?;
+
+    // Try to open the file automatically
+    #[cfg(target_os = "windows")]
+    std::process::Command::new("cmd")
+        .args(["/C", "start", output_path])
+        .spawn()?;
+
+    #[cfg(target_os = "macos")]
+    std::process::Command::new("open")
+        .arg(output_path)
+        .spawn()?;
+
+    #[cfg(target_os = "linux")]
+    std::process::Command::new("xdg-open")
+        .arg(output_path)
+        .spawn()?;
+
+    Ok(())
+}
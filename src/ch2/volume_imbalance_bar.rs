@@ -1,107 +1,14 @@
 use crate::config;
 use crate::base::{Bar, read_trades, Trade};
 use crate::ch2::time_bar::compute_time_bars;
+use crate::ch2::bar_builder::{ClosingRule, compute_information_bars, volume_metric};
 use std::error::Error;
-use chrono::{Utc, TimeZone};
 use plotters::prelude::*;
 
 pub fn compute_volume_imbalance_bars(trades: &[Trade], initial_expected_volume: f64) -> Vec<Bar> {
-    if trades.is_empty() {
-        return Vec::new();
-    }
-
-    let mut bars = Vec::new();
-    let mut current_imbalance: f64 = 0.0;
-    let mut current_volume: f64 = 0.0;
-    
-    let mut open = trades[0].price;
-    let mut high = trades[0].price;
-    let mut low = trades[0].price;
-    let mut close;
-    let mut volume = 0.0;
-    
-    let mut prev_price = trades[0].price;
-    let mut prev_tick_rule = 1.0; // Init as buy
-    
-    let mut is_new_bar = true;
-
-    // EWMA parameters
-    let alpha = 0.05; // Smoothing factor
-    let mut ewma_expected_volume = initial_expected_volume;
-    let mut ewma_expected_imbalance_per_tick: f64 = 0.5; // Initial guess for |2P[b=1]-1|
-
-    // For calculating imbalance per tick (or per unit), we need to track ticks or just use the ratio
-    // In standard implementation, we track expected imbalance (theta).
-    // Threshold = E[V] * |2P[b=1] - 1|
-    // Here we update |2P[b=1] - 1| based on observed imbalance.
-
-    for trade in trades {
-        if is_new_bar {
-            open = trade.price;
-            high = trade.price;
-            low = trade.price;
-            volume = 0.0;
-            is_new_bar = false;
-            current_volume = 0.0;
-        }
-
-        // 1. Calculate Tick Rule
-        let tick_rule = if trade.price > prev_price {
-            1.0
-        } else if trade.price < prev_price {
-            -1.0
-        } else {
-            prev_tick_rule
-        };
-        prev_tick_rule = tick_rule;
-        prev_price = trade.price;
-
-        // 2. Accumulate Imbalance (Volume * Tick Rule)
-        let signed_volume = tick_rule * trade.amount;
-        current_imbalance += signed_volume;
-        current_volume += trade.amount;
-
-        // Update Bar stats
-        high = high.max(trade.price);
-        low = low.min(trade.price);
-        close = trade.price;
-        volume += trade.amount;
-
-        // 3. Check Threshold
-        // Threshold = E[V] * |2P[b=1] - 1|
-        let threshold = ewma_expected_volume * ewma_expected_imbalance_per_tick.abs();
-        
-        if current_imbalance.abs() >= threshold {
-            bars.push(Bar {
-                time: Utc.timestamp_micros(trade.timestamp as i64).unwrap(),
-                open,
-                high,
-                low,
-                close,
-                volume,
-            });
-
-            // Update EWMA
-            ewma_expected_volume = alpha * current_volume + (1.0 - alpha) * ewma_expected_volume;
-            
-            // Update expected imbalance ratio. 
-            // We can approximate |2P[b=1]-1| as |current_imbalance / current_volume|
-            // Since current_imbalance = sum(b_i * v_i) and current_volume = sum(v_i)
-            let observed_imbalance_ratio = if current_volume > 0.0 {
-                current_imbalance / current_volume
-            } else {
-                0.0
-            };
-            
-            ewma_expected_imbalance_per_tick = alpha * observed_imbalance_ratio + (1.0 - alpha) * ewma_expected_imbalance_per_tick;
-
-            // Reset
-            current_imbalance = 0.0;
-            is_new_bar = true;
-        }
-    }
-    
-    bars
+    // Threshold = E[V] * |2P[b=1] - 1|, EWMA alpha 0.05
+    let rule = ClosingRule::Imbalance { initial_expected: initial_expected_volume, alpha: 0.05 };
+    compute_information_bars(trades, volume_metric, rule)
 }
 
 pub fn draw_volume_imbalance_bar() -> Result<(), Box<dyn Error>> {
@@ -118,7 +25,7 @@ pub fn draw_volume_imbalance_bar() -> Result<(), Box<dyn Error>> {
 
     // 2. Compute Volume Imbalance Bars
     // Initial guess: Total Volume / 300
-    let total_volume: f64 = trades.iter().map(|t| t.amount).sum();
+    let total_volume: f64 = trades.iter().map(volume_metric).sum();
     let initial_expected_volume = total_volume / 300.0;
     
     println!("Computing Volume Imbalance Bars with dynamic threshold (init V={:.2})...", initial_expected_volume);
@@ -204,8 +111,8 @@ fn draw_overlay_chart(
     .legend(|(x, y)| Circle::new((x + 10, y), 4, BLUE.filled()));
 
     chart.configure_series_labels()
-        .background_style(&WHITE.mix(0.8))
-        .border_style(&BLACK)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
         .draw()?;
 
     Ok(())
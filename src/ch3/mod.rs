@@ -0,0 +1,3 @@
+pub mod volatility;
+
+pub use volatility::run_volatility;
@@ -0,0 +1,256 @@
+use crate::config;
+use crate::base::{Bar, read_trades};
+use crate::ch2::time_bar::compute_time_bars;
+use crate::ch2::cusum_filter::compute_cusum_events;
+use std::error::Error;
+use chrono::{DateTime, Duration, Utc};
+use plotters::prelude::*;
+
+// Per-bar volatility estimators, all in log-return units
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Estimator {
+    CloseToClose,   // std of close-to-close log returns
+    Parkinson,      // high-low range
+    GarmanKlass,    // range + open-to-close
+    RogersSatchell, // drift-independent range
+    YangZhang,      // overnight + open-to-close + Rogers-Satchell
+}
+
+impl Estimator {
+    pub const ALL: [Estimator; 5] = [
+        Estimator::CloseToClose,
+        Estimator::Parkinson,
+        Estimator::GarmanKlass,
+        Estimator::RogersSatchell,
+        Estimator::YangZhang,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Estimator::CloseToClose => "close_to_close",
+            Estimator::Parkinson => "parkinson",
+            Estimator::GarmanKlass => "garman_klass",
+            Estimator::RogersSatchell => "rogers_satchell",
+            Estimator::YangZhang => "yang_zhang",
+        }
+    }
+}
+
+fn sample_variance(values: &[f64]) -> f64 {
+    let n = values.len() as f64;
+    if n < 2.0 {
+        return 0.0;
+    }
+    let mean = values.iter().sum::<f64>() / n;
+    values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)
+}
+
+fn log_ratio(a: f64, b: f64) -> f64 {
+    if a > 0.0 && b > 0.0 { (a / b).ln() } else { 0.0 }
+}
+
+// Full-sample volatility of the bars
+pub fn volatility(bars: &[Bar], estimator: Estimator) -> f64 {
+    let n = bars.len() as f64;
+    if bars.is_empty() {
+        return 0.0;
+    }
+    let variance = match estimator {
+        Estimator::CloseToClose => {
+            let returns: Vec<f64> = bars.windows(2).map(|w| log_ratio(w[1].close, w[0].close)).collect();
+            sample_variance(&returns)
+        }
+        Estimator::Parkinson => {
+            bars.iter().map(|b| log_ratio(b.high, b.low).powi(2)).sum::<f64>() / (4.0 * 2f64.ln() * n)
+        }
+        Estimator::GarmanKlass => {
+            bars.iter().map(|b| {
+                0.5 * log_ratio(b.high, b.low).powi(2) - (2.0 * 2f64.ln() - 1.0) * log_ratio(b.close, b.open).powi(2)
+            }).sum::<f64>() / n
+        }
+        Estimator::RogersSatchell => rogers_satchell_variance(bars),
+        Estimator::YangZhang => {
+            if bars.len() < 3 {
+                return 0.0;
+            }
+            let overnight: Vec<f64> = bars.windows(2).map(|w| log_ratio(w[1].open, w[0].close)).collect();
+            let open_close: Vec<f64> = bars[1..].iter().map(|b| log_ratio(b.close, b.open)).collect();
+            let m = open_close.len() as f64;
+            let k = 0.34 / (1.34 + (m + 1.0) / (m - 1.0));
+            sample_variance(&overnight) + k * sample_variance(&open_close) + (1.0 - k) * rogers_satchell_variance(&bars[1..])
+        }
+    };
+    variance.max(0.0).sqrt()
+}
+
+fn rogers_satchell_variance(bars: &[Bar]) -> f64 {
+    bars.iter().map(|b| {
+        log_ratio(b.high, b.close) * log_ratio(b.high, b.open) + log_ratio(b.low, b.close) * log_ratio(b.low, b.open)
+    }).sum::<f64>() / bars.len() as f64
+}
+
+// Volatility over the last `window` bars at every bar; None until the window is full
+pub fn rolling_volatility(bars: &[Bar], estimator: Estimator, window: usize) -> Vec<Option<f64>> {
+    (0..bars.len())
+        .map(|i| (i + 1 >= window).then(|| volatility(&bars[i + 1 - window..=i], estimator)))
+        .collect()
+}
+
+// Exponentially weighted std of returns, alpha = 2 / (span + 1), bias-corrected like pandas ewm().std()
+pub fn ewma_std(returns: &[f64], span: usize) -> Vec<f64> {
+    let alpha = 2.0 / (span as f64 + 1.0);
+    let mut result = Vec::with_capacity(returns.len());
+    let (mut sum_w, mut sum_w2) = (0.0, 0.0);
+    let (mut mean, mut var) = (0.0, 0.0);
+
+    for (i, &r) in returns.iter().enumerate() {
+        if i == 0 {
+            sum_w = 1.0;
+            sum_w2 = 1.0;
+            mean = r;
+            result.push(0.0);
+            continue;
+        }
+        sum_w = (1.0 - alpha) * sum_w + 1.0;
+        sum_w2 = (1.0 - alpha).powi(2) * sum_w2 + 1.0;
+        let w = 1.0 / sum_w;
+        let delta = r - mean;
+        mean += w * delta;
+        var = (1.0 - w) * (var + w * delta * delta);
+        let bias = sum_w * sum_w / (sum_w * sum_w - sum_w2);
+        result.push((var * bias).max(0.0).sqrt());
+    }
+    result
+}
+
+// Daily-vol style estimate (AFML getDailyVol): returns over `lookback` (e.g. one day),
+// smoothed with an EWMA std of the given span. Aligned with bars; 0 until a return exists.
+pub fn daily_volatility(bars: &[Bar], lookback: Duration, span: usize) -> Vec<f64> {
+    let mut index = Vec::new();
+    let mut returns = Vec::new();
+    let mut j = 0;
+    for (i, bar) in bars.iter().enumerate() {
+        while j + 1 < i && bars[j + 1].time <= bar.time - lookback {
+            j += 1;
+        }
+        if i > 0 && bars[j].time <= bar.time - lookback {
+            index.push(i);
+            returns.push(bar.close / bars[j].close - 1.0);
+        }
+    }
+
+    let smoothed = ewma_std(&returns, span);
+    let mut result = vec![0.0; bars.len()];
+    for (k, &i) in index.iter().enumerate() {
+        result[i] = smoothed[k];
+    }
+    result
+}
+
+pub fn run_volatility() -> Result<(), Box<dyn Error>> {
+    let file_path = config::TARDIS_CSV_PATH;
+    println!("Reading trades from {}...", file_path);
+    let trades = read_trades(file_path)?;
+    println!("Read {} trades.", trades.len());
+
+    // 1. Compute Time Bars (5 minutes)
+    let time_interval_minutes = 5;
+    println!("Computing {} minute time bars...", time_interval_minutes);
+    let bars = compute_time_bars(&trades, time_interval_minutes);
+    println!("Generated {} time bars.", bars.len());
+    if bars.len() < 3 {
+        return Err("Not enough bars".into());
+    }
+
+    // 2. Full-sample estimates, each used as a CUSUM threshold
+    println!("--- Full-sample volatility (per bar) ---");
+    for estimator in Estimator::ALL {
+        let vol = volatility(&bars, estimator);
+        let events = compute_cusum_events(&bars, vol);
+        println!("{:<16} {:.6}  -> {} CUSUM events", estimator.name(), vol, events.len());
+    }
+
+    // 3. Rolling estimates and EWMA daily-vol style estimate, as features
+    let window = 20;
+    let rolling: Vec<Vec<Option<f64>>> = Estimator::ALL.iter()
+        .map(|&e| rolling_volatility(&bars, e, window))
+        .collect();
+    let ewma = daily_volatility(&bars, Duration::hours(1), 20);
+
+    std::fs::create_dir_all("src/ch3/result")?;
+    let csv_path = "src/ch3/result/volatility.csv";
+    let mut wtr = csv::Writer::from_path(csv_path)?;
+    let mut header = vec!["time".to_string()];
+    header.extend(Estimator::ALL.iter().map(|e| e.name().to_string()));
+    header.push("ewma_1h".to_string());
+    wtr.write_record(&header)?;
+    for (i, bar) in bars.iter().enumerate() {
+        let mut record = vec![bar.time.to_rfc3339()];
+        record.extend(rolling.iter().map(|r| r[i].map(|v| v.to_string()).unwrap_or_default()));
+        record.push(ewma[i].to_string());
+        wtr.write_record(&record)?;
+    }
+    wtr.flush()?;
+    println!("Rolling volatility ({} bars) saved to {}", window, csv_path);
+
+    // 4. Draw Chart
+    let output_path = "src/ch3/result/volatility.png";
+    println!("Drawing chart to {}...", output_path);
+    draw_volatility_chart(&bars, &rolling, output_path)?;
+    println!("Chart saved to {}", output_path);
+
+    // Open file
+    #[cfg(target_os = "windows")]
+    std::process::Command::new("cmd")
+        .args(["/C", "start", output_path])
+        .spawn()?;
+
+    #[cfg(target_os = "macos")]
+    std::process::Command::new("open")
+        .arg(output_path)
+        .spawn()?;
+
+    #[cfg(target_os = "linux")]
+    std::process::Command::new("xdg-open")
+        .arg(output_path)
+        .spawn()?;
+
+    Ok(())
+}
+
+// rolling[k] holds the values of Estimator::ALL[k]
+fn draw_volatility_chart(bars: &[Bar], rolling: &[Vec<Option<f64>>], output_path: &str) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(output_path, (1280, 720)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let series: Vec<Vec<(DateTime<Utc>, f64)>> = rolling.iter()
+        .map(|r| bars.iter().zip(r).filter_map(|(b, v)| v.map(|v| (b.time, v))).collect())
+        .collect();
+    let points = series.iter().flatten();
+    let min_time = points.clone().map(|p| p.0).min().ok_or("No volatility values")?;
+    let max_time = points.clone().map(|p| p.0).max().unwrap();
+    let max_vol = points.map(|p| p.1).fold(0.0, f64::max);
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Rolling Volatility Estimators", ("sans-serif", 30).into_font())
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(80)
+        .build_cartesian_2d(min_time..max_time, 0.0..max_vol * 1.1)?;
+
+    chart.configure_mesh().y_desc("Volatility per bar").draw()?;
+
+    for (k, (estimator, s)) in Estimator::ALL.iter().zip(&series).enumerate() {
+        let color = Palette99::pick(k).to_rgba();
+        chart.draw_series(LineSeries::new(s.iter().copied(), color.stroke_width(2)))?
+            .label(estimator.name())
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+    }
+
+    chart.configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+
+    Ok(())
+}
@@ -0,0 +1,286 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+
+pub trait Classifier {
+    // x: one row per sample, y: class labels, sample_weight: one weight per sample
+    fn fit(&mut self, x: &[Vec<f64>], y: &[i32], sample_weight: &[f64]);
+
+    // Sorted class labels seen in fit; predict_proba columns follow this order
+    fn classes(&self) -> &[i32];
+
+    fn predict_proba(&self, x: &[Vec<f64>]) -> Vec<Vec<f64>>;
+
+    fn predict(&self, x: &[Vec<f64>]) -> Vec<i32> {
+        let classes = self.classes();
+        self.predict_proba(x).iter().map(|proba| {
+            let best = proba.iter().enumerate()
+                .fold(0, |best, (k, p)| if *p > proba[best] { k } else { best });
+            classes[best]
+        }).collect()
+    }
+
+    // Ensembles reseed their members so each one draws different random numbers
+    fn reseed(&mut self, _seed: u64) {}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Criterion {
+    Gini,
+    Entropy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClassWeight {
+    None,
+    Balanced, // n_samples / (n_classes * count(class)) on the data passed to fit
+}
+
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub feature: Option<usize>, // None for leaves
+    pub threshold: f64,         // go left when x[feature] <= threshold
+    pub left: usize,
+    pub right: usize,
+    pub impurity: f64,
+    pub weight: f64,            // total sample weight reaching the node
+    pub value: Vec<f64>,        // class probabilities at the node
+}
+
+// CART decision tree
+#[derive(Debug, Clone)]
+pub struct DecisionTree {
+    pub criterion: Criterion,
+    pub max_depth: Option<usize>,
+    pub min_samples_leaf: usize,
+    pub max_features: Option<usize>, // random subset of features tried at each split
+    pub class_weight: ClassWeight,
+    pub seed: u64,
+    pub nodes: Vec<Node>,
+    pub n_features: usize,
+    classes: Vec<i32>,
+}
+
+impl DecisionTree {
+    pub fn new(criterion: Criterion, max_depth: Option<usize>, min_samples_leaf: usize) -> Self {
+        DecisionTree {
+            criterion,
+            max_depth,
+            min_samples_leaf: min_samples_leaf.max(1),
+            max_features: None,
+            class_weight: ClassWeight::None,
+            seed: 0,
+            nodes: Vec::new(),
+            n_features: 0,
+            classes: Vec::new(),
+        }
+    }
+
+    fn impurity(&self, counts: &[f64], total: f64) -> f64 {
+        if total <= 0.0 {
+            return 0.0;
+        }
+        match self.criterion {
+            Criterion::Gini => 1.0 - counts.iter().map(|c| (c / total).powi(2)).sum::<f64>(),
+            Criterion::Entropy => -counts.iter()
+                .filter(|c| **c > 0.0)
+                .map(|c| (c / total) * (c / total).log2())
+                .sum::<f64>(),
+        }
+    }
+
+    fn build(
+        &mut self,
+        x: &[Vec<f64>],
+        y_idx: &[usize],
+        w: &[f64],
+        samples: Vec<usize>,
+        depth: usize,
+        rng: &mut StdRng,
+    ) -> usize {
+        let n_classes = self.classes.len();
+        let mut counts = vec![0.0; n_classes];
+        for &i in &samples {
+            counts[y_idx[i]] += w[i];
+        }
+        let total: f64 = counts.iter().sum();
+        let impurity = self.impurity(&counts, total);
+        let value = counts.iter().map(|c| if total > 0.0 { c / total } else { 1.0 / n_classes as f64 }).collect();
+
+        let id = self.nodes.len();
+        self.nodes.push(Node { feature: None, threshold: 0.0, left: 0, right: 0, impurity, weight: total, value });
+
+        let depth_reached = self.max_depth.is_some_and(|d| depth >= d);
+        if depth_reached || samples.len() < 2 * self.min_samples_leaf || impurity <= 0.0 {
+            return id;
+        }
+
+        // Candidate features
+        let features: Vec<usize> = match self.max_features {
+            Some(k) if k < self.n_features => sample(rng, self.n_features, k.max(1)).into_vec(),
+            _ => (0..self.n_features).collect(),
+        };
+
+        // Best split: lowest weighted child impurity
+        let mut best: Option<(usize, f64, f64)> = None; // (feature, threshold, child impurity)
+        for &f in &features {
+            let mut sorted = samples.clone();
+            sorted.sort_by(|&a, &b| x[a][f].total_cmp(&x[b][f]));
+
+            let mut left = vec![0.0; n_classes];
+            let mut left_total = 0.0;
+            for k in 0..sorted.len() - 1 {
+                let i = sorted[k];
+                left[y_idx[i]] += w[i];
+                left_total += w[i];
+
+                let n_left = k + 1;
+                if n_left < self.min_samples_leaf || sorted.len() - n_left < self.min_samples_leaf {
+                    continue;
+                }
+                let (v, v_next) = (x[i][f], x[sorted[k + 1]][f]);
+                if v == v_next {
+                    continue;
+                }
+
+                let right: Vec<f64> = counts.iter().zip(&left).map(|(c, l)| c - l).collect();
+                let right_total = total - left_total;
+                let child = (left_total * self.impurity(&left, left_total)
+                    + right_total * self.impurity(&right, right_total)) / total;
+                if best.is_none_or(|(_, _, b)| child < b) {
+                    best = Some((f, (v + v_next) / 2.0, child));
+                }
+            }
+        }
+
+        let Some((feature, threshold, child)) = best else {
+            return id;
+        };
+        if child >= impurity {
+            return id;
+        }
+
+        let (left_samples, right_samples): (Vec<usize>, Vec<usize>) =
+            samples.into_iter().partition(|&i| x[i][feature] <= threshold);
+        let left = self.build(x, y_idx, w, left_samples, depth + 1, rng);
+        let right = self.build(x, y_idx, w, right_samples, depth + 1, rng);
+
+        let node = &mut self.nodes[id];
+        node.feature = Some(feature);
+        node.threshold = threshold;
+        node.left = left;
+        node.right = right;
+        id
+    }
+
+    // Mean decrease impurity: weighted impurity decrease of every split, by feature, normalized to sum to 1
+    pub fn feature_importances(&self) -> Vec<f64> {
+        let mut importances = vec![0.0; self.n_features];
+        for node in &self.nodes {
+            if let Some(f) = node.feature {
+                let (left, right) = (&self.nodes[node.left], &self.nodes[node.right]);
+                importances[f] += node.weight * node.impurity
+                    - left.weight * left.impurity
+                    - right.weight * right.impurity;
+            }
+        }
+        let total: f64 = importances.iter().sum();
+        if total > 0.0 {
+            importances.iter_mut().for_each(|v| *v /= total);
+        }
+        importances
+    }
+
+    fn leaf(&self, row: &[f64]) -> &Node {
+        let mut node = &self.nodes[0];
+        while let Some(f) = node.feature {
+            node = if row[f] <= node.threshold { &self.nodes[node.left] } else { &self.nodes[node.right] };
+        }
+        node
+    }
+}
+
+// Sorted distinct labels and the index of each sample's label
+pub fn encode_labels(y: &[i32]) -> (Vec<i32>, Vec<usize>) {
+    let mut classes = y.to_vec();
+    classes.sort();
+    classes.dedup();
+    let idx = y.iter().map(|c| classes.binary_search(c).unwrap()).collect();
+    (classes, idx)
+}
+
+// Multiply sample weights by n_samples / (n_classes * count(class))
+pub fn balanced_weights(y_idx: &[usize], n_classes: usize, sample_weight: &[f64]) -> Vec<f64> {
+    let mut counts = vec![0usize; n_classes];
+    for &c in y_idx {
+        counts[c] += 1;
+    }
+    let n = y_idx.len() as f64;
+    y_idx.iter().zip(sample_weight)
+        .map(|(&c, w)| w * n / (n_classes as f64 * counts[c] as f64))
+        .collect()
+}
+
+impl Classifier for DecisionTree {
+    fn fit(&mut self, x: &[Vec<f64>], y: &[i32], sample_weight: &[f64]) {
+        let (classes, y_idx) = encode_labels(y);
+        self.classes = classes;
+        self.n_features = x.first().map(|r| r.len()).unwrap_or(0);
+        self.nodes.clear();
+
+        let w = match self.class_weight {
+            ClassWeight::None => sample_weight.to_vec(),
+            ClassWeight::Balanced => balanced_weights(&y_idx, self.classes.len(), sample_weight),
+        };
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        self.build(x, &y_idx, &w, (0..x.len()).collect(), 0, &mut rng);
+    }
+
+    fn classes(&self) -> &[i32] {
+        &self.classes
+    }
+
+    fn predict_proba(&self, x: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        x.iter().map(|row| self.leaf(row).value.clone()).collect()
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+}
+
+pub fn accuracy(y_true: &[i32], y_pred: &[i32], sample_weight: &[f64]) -> f64 {
+    let total: f64 = sample_weight.iter().sum();
+    let correct: f64 = y_true.iter().zip(y_pred).zip(sample_weight)
+        .filter(|((t, p), _)| t == p)
+        .map(|(_, w)| w)
+        .sum();
+    if total > 0.0 { correct / total } else { 0.0 }
+}
+
+// Weighted cross-entropy of predicted probabilities (columns ordered by classes)
+pub fn log_loss(y_true: &[i32], proba: &[Vec<f64>], classes: &[i32], sample_weight: &[f64]) -> f64 {
+    let eps = 1e-15;
+    let total: f64 = sample_weight.iter().sum();
+    let loss: f64 = y_true.iter().zip(proba).zip(sample_weight).map(|((t, p), w)| {
+        let k = classes.iter().position(|c| c == t);
+        let prob = k.map(|k| p[k]).unwrap_or(0.0).clamp(eps, 1.0 - eps);
+        -w * prob.ln()
+    }).sum();
+    if total > 0.0 { loss / total } else { 0.0 }
+}
+
+// Weighted F1 score of the positive class
+pub fn f1_score(y_true: &[i32], y_pred: &[i32], positive: i32, sample_weight: &[f64]) -> f64 {
+    let (mut tp, mut fp, mut fn_) = (0.0, 0.0, 0.0);
+    for ((t, p), w) in y_true.iter().zip(y_pred).zip(sample_weight) {
+        match (*t == positive, *p == positive) {
+            (true, true) => tp += w,
+            (false, true) => fp += w,
+            (true, false) => fn_ += w,
+            (false, false) => {}
+        }
+    }
+    if tp == 0.0 { 0.0 } else { 2.0 * tp / (2.0 * tp + fp + fn_) }
+}
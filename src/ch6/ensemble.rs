@@ -0,0 +1,246 @@
+use crate::config;
+use crate::base::read_trades;
+use crate::ch2::bar_builder::dollar_metric;
+use crate::ch2::dollar_bar::compute_dollar_bars;
+use crate::ch6::classifier::{
+    Classifier, ClassWeight, Criterion, DecisionTree, accuracy, balanced_weights, encode_labels, log_loss,
+};
+use crate::ch6::features::{FEATURE_NAMES, build_dataset, label_uniqueness};
+use std::error::Error;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+
+// Bagging over clones of a base classifier.
+// max_samples is the fraction of samples drawn for each estimator; AFML sets it to the
+// average label uniqueness so that overlapping labels are not oversampled.
+#[derive(Debug, Clone)]
+pub struct BaggingClassifier<C: Classifier + Clone> {
+    pub base: C,
+    pub n_estimators: usize,
+    pub max_samples: f64,
+    pub bootstrap: bool,
+    pub seed: u64,
+    pub estimators: Vec<C>,
+    classes: Vec<i32>,
+}
+
+impl<C: Classifier + Clone> BaggingClassifier<C> {
+    pub fn new(base: C, n_estimators: usize, max_samples: f64, seed: u64) -> Self {
+        BaggingClassifier {
+            base,
+            n_estimators,
+            max_samples,
+            bootstrap: true,
+            seed,
+            estimators: Vec::new(),
+            classes: Vec::new(),
+        }
+    }
+}
+
+impl<C: Classifier + Clone> Classifier for BaggingClassifier<C> {
+    fn fit(&mut self, x: &[Vec<f64>], y: &[i32], sample_weight: &[f64]) {
+        let (classes, _) = encode_labels(y);
+        self.classes = classes;
+        self.estimators.clear();
+
+        let n = x.len();
+        if n == 0 {
+            return; // Empty training set: no estimators, no classes
+        }
+        let n_draw = ((self.max_samples * n as f64).round() as usize).clamp(1, n);
+        let mut rng = StdRng::seed_from_u64(self.seed);
+
+        for _ in 0..self.n_estimators {
+            let idx: Vec<usize> = if self.bootstrap {
+                (0..n_draw).map(|_| rng.gen_range(0..n)).collect()
+            } else {
+                sample(&mut rng, n, n_draw).into_vec()
+            };
+            let xs: Vec<Vec<f64>> = idx.iter().map(|&i| x[i].clone()).collect();
+            let ys: Vec<i32> = idx.iter().map(|&i| y[i]).collect();
+            let ws: Vec<f64> = idx.iter().map(|&i| sample_weight[i]).collect();
+
+            let mut estimator = self.base.clone();
+            estimator.reseed(rng.r#gen());
+            estimator.fit(&xs, &ys, &ws);
+            self.estimators.push(estimator);
+        }
+    }
+
+    fn classes(&self) -> &[i32] {
+        &self.classes
+    }
+
+    // Average of the estimators' probabilities; classes an estimator never saw count as 0
+    fn predict_proba(&self, x: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        let mut proba = vec![vec![0.0; self.classes.len()]; x.len()];
+        for estimator in &self.estimators {
+            let cols: Vec<usize> = estimator.classes().iter()
+                .map(|c| self.classes.binary_search(c).unwrap())
+                .collect();
+            for (row, p) in proba.iter_mut().zip(estimator.predict_proba(x)) {
+                for (k, v) in cols.iter().zip(p) {
+                    row[*k] += v;
+                }
+            }
+        }
+        let m = self.estimators.len().max(1) as f64;
+        for row in proba.iter_mut() {
+            row.iter_mut().for_each(|v| *v /= m);
+        }
+        proba
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ForestClassWeight {
+    None,
+    Balanced,          // computed once on the full training set
+    BalancedSubsample, // recomputed on each tree's bootstrap sample
+}
+
+// Random forest: bagged trees that also subsample features at every split
+#[derive(Debug, Clone)]
+pub struct RandomForest {
+    pub n_estimators: usize,
+    pub criterion: Criterion,
+    pub max_depth: Option<usize>,
+    pub min_samples_leaf: usize,
+    pub max_features: Option<usize>, // None: sqrt(n_features)
+    pub max_samples: f64,
+    pub class_weight: ForestClassWeight,
+    pub seed: u64,
+    pub bagging: Option<BaggingClassifier<DecisionTree>>,
+}
+
+impl RandomForest {
+    pub fn new(n_estimators: usize, max_samples: f64, class_weight: ForestClassWeight, seed: u64) -> Self {
+        RandomForest {
+            n_estimators,
+            criterion: Criterion::Entropy,
+            max_depth: None,
+            min_samples_leaf: 1,
+            max_features: None,
+            max_samples,
+            class_weight,
+            seed,
+            bagging: None,
+        }
+    }
+}
+
+impl Classifier for RandomForest {
+    fn fit(&mut self, x: &[Vec<f64>], y: &[i32], sample_weight: &[f64]) {
+        let n_features = x.first().map(|r| r.len()).unwrap_or(0);
+        let mut tree = DecisionTree::new(self.criterion, self.max_depth, self.min_samples_leaf);
+        tree.max_features = Some(self.max_features.unwrap_or((n_features as f64).sqrt().round() as usize).max(1));
+
+        let weights = match self.class_weight {
+            ForestClassWeight::None => sample_weight.to_vec(),
+            ForestClassWeight::Balanced => {
+                let (classes, y_idx) = encode_labels(y);
+                balanced_weights(&y_idx, classes.len(), sample_weight)
+            }
+            ForestClassWeight::BalancedSubsample => {
+                tree.class_weight = ClassWeight::Balanced;
+                sample_weight.to_vec()
+            }
+        };
+
+        let mut bagging = BaggingClassifier::new(tree, self.n_estimators, self.max_samples, self.seed);
+        bagging.fit(x, y, &weights);
+        self.bagging = Some(bagging);
+    }
+
+    fn classes(&self) -> &[i32] {
+        self.bagging.as_ref().map(|b| b.classes()).unwrap_or(&[])
+    }
+
+    fn predict_proba(&self, x: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        match &self.bagging {
+            Some(b) => b.predict_proba(x),
+            None => Vec::new(),
+        }
+    }
+
+    fn reseed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+}
+
+pub fn run_ensemble_methods() -> Result<(), Box<dyn Error>> {
+    let file_path = config::TARDIS_CSV_PATH;
+    println!("Reading trades from {}...", file_path);
+    let trades = read_trades(file_path)?;
+    println!("Read {} trades.", trades.len());
+
+    // 1. Compute Dollar Bars (~1000 bars)
+    let total_dollar: f64 = trades.iter().map(dollar_metric).sum();
+    let dollar_interval = total_dollar / 1000.0;
+    println!("Computing {:.2} dollar bars...", dollar_interval);
+    let bars = compute_dollar_bars(&trades, dollar_interval);
+    println!("Generated {} dollar bars.", bars.len());
+
+    // 2. Features, labels and uniqueness weights
+    let horizon = 5;
+    let dataset = build_dataset(&bars, horizon);
+    if dataset.x.len() < 10 {
+        return Err("Not enough bars to build a dataset".into());
+    }
+    let uniqueness = label_uniqueness(&dataset.spans);
+    let avg_uniqueness = uniqueness.iter().sum::<f64>() / uniqueness.len() as f64;
+    println!(
+        "Built {} samples with {} features ({}-bar labels, average uniqueness {:.4}).",
+        dataset.x.len(), FEATURE_NAMES.len(), horizon, avg_uniqueness
+    );
+
+    // 3. Chronological split, dropping training labels that overlap the test set
+    let split = dataset.x.len() * 7 / 10;
+    let test_start_bar = dataset.spans[split].0;
+    let train: Vec<usize> = (0..split).filter(|&i| dataset.spans[i].1 < test_start_bar).collect();
+    let test: Vec<usize> = (split..dataset.x.len()).collect();
+
+    let rows = |idx: &[usize]| -> (Vec<Vec<f64>>, Vec<i32>, Vec<f64>) {
+        (
+            idx.iter().map(|&i| dataset.x[i].clone()).collect(),
+            idx.iter().map(|&i| dataset.y[i]).collect(),
+            idx.iter().map(|&i| uniqueness[i]).collect(),
+        )
+    };
+    let (x_train, y_train, w_train) = rows(&train);
+    let (x_test, y_test, _) = rows(&test);
+    let w_test = vec![1.0; x_test.len()];
+    println!("Train: {} samples, test: {} samples.", x_train.len(), x_test.len());
+
+    // 4. Fit and evaluate
+    let bagged_tree = DecisionTree::new(Criterion::Entropy, None, 1);
+    let mut models: Vec<(&str, Box<dyn Classifier>)> = vec![
+        ("Decision Tree (gini)", Box::new(DecisionTree::new(Criterion::Gini, Some(5), 10))),
+        ("Decision Tree (entropy)", Box::new(DecisionTree::new(Criterion::Entropy, Some(5), 10))),
+        ("Bagging (max_samples = avgU)", Box::new(BaggingClassifier::new(bagged_tree, 100, avg_uniqueness, 42))),
+        ("Random Forest (no class weight)", Box::new(RandomForest::new(100, avg_uniqueness, ForestClassWeight::None, 42))),
+        ("Random Forest (balanced)", Box::new(RandomForest::new(100, avg_uniqueness, ForestClassWeight::Balanced, 42))),
+        ("Random Forest (balanced_subsample)", Box::new(RandomForest::new(100, avg_uniqueness, ForestClassWeight::BalancedSubsample, 42))),
+    ];
+
+    println!("--- Out-of-sample Results ---");
+    for (name, model) in models.iter_mut() {
+        model.fit(&x_train, &y_train, &w_train);
+        let pred = model.predict(&x_test);
+        let proba = model.predict_proba(&x_test);
+        println!(
+            "{}: accuracy = {:.4}, log loss = {:.4}",
+            name,
+            accuracy(&y_test, &pred, &w_test),
+            log_loss(&y_test, &proba, model.classes(), &w_test)
+        );
+    }
+
+    Ok(())
+}
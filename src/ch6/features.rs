@@ -0,0 +1,78 @@
+use crate::base::Bar;
+
+pub const FEATURE_NAMES: [&str; 7] = [
+    "ret_1",
+    "ret_5",
+    "ret_20",
+    "volatility_20",
+    "log_volume_ratio_20",
+    "range",
+    "body",
+];
+
+const LOOKBACK: usize = 20;
+
+// Labeled samples built from a bar series
+#[derive(Debug, Clone)]
+pub struct Dataset {
+    pub x: Vec<Vec<f64>>,
+    pub y: Vec<i32>,
+    pub spans: Vec<(usize, usize)>, // bar index where each label starts and ends
+    pub bar_index: Vec<usize>,      // bar each row was observed at
+}
+
+fn log_return(bars: &[Bar], from: usize, to: usize) -> f64 {
+    (bars[to].close / bars[from].close).ln()
+}
+
+// Features observed at the close of bar i (needs LOOKBACK bars of history)
+pub fn bar_features(bars: &[Bar], i: usize) -> Vec<f64> {
+    let returns: Vec<f64> = (i + 1 - LOOKBACK..=i).map(|k| log_return(bars, k - 1, k)).collect();
+    let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+    let volatility = (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64).sqrt();
+
+    let avg_volume = bars[i + 1 - LOOKBACK..=i].iter().map(|b| b.volume).sum::<f64>() / LOOKBACK as f64;
+    let volume_ratio = if avg_volume > 0.0 && bars[i].volume > 0.0 { (bars[i].volume / avg_volume).ln() } else { 0.0 };
+
+    let bar = &bars[i];
+    vec![
+        log_return(bars, i - 1, i),
+        log_return(bars, i - 5, i),
+        log_return(bars, i - LOOKBACK, i),
+        volatility,
+        volume_ratio,
+        (bar.high - bar.low) / bar.close,
+        (bar.close - bar.open) / bar.close,
+    ]
+}
+
+// Label = sign of the log return over the next `horizon` bars (1 for up, -1 otherwise)
+pub fn build_dataset(bars: &[Bar], horizon: usize) -> Dataset {
+    let mut dataset = Dataset { x: Vec::new(), y: Vec::new(), spans: Vec::new(), bar_index: Vec::new() };
+    if bars.len() <= LOOKBACK + horizon {
+        return dataset;
+    }
+
+    for i in LOOKBACK..bars.len() - horizon {
+        dataset.x.push(bar_features(bars, i));
+        dataset.y.push(if log_return(bars, i, i + horizon) > 0.0 { 1 } else { -1 });
+        dataset.spans.push((i + 1, i + horizon));
+        dataset.bar_index.push(i);
+    }
+    dataset
+}
+
+// Average uniqueness of each label over its lifespan: mean of 1 / concurrency
+pub fn label_uniqueness(spans: &[(usize, usize)]) -> Vec<f64> {
+    let end = spans.iter().map(|(_, t1)| *t1).max().unwrap_or(0);
+    let mut concurrency = vec![0.0; end + 1];
+    for &(t0, t1) in spans {
+        for c in &mut concurrency[t0..=t1] {
+            *c += 1.0;
+        }
+    }
+    spans.iter().map(|&(t0, t1)| {
+        let sum: f64 = concurrency[t0..=t1].iter().map(|c| 1.0 / c).sum();
+        sum / (t1 - t0 + 1) as f64
+    }).collect()
+}
@@ -0,0 +1,5 @@
+pub mod classifier;
+pub mod ensemble;
+pub mod features;
+
+pub use ensemble::run_ensemble_methods;
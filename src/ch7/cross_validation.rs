@@ -0,0 +1,68 @@
+use crate::ch6::classifier::{Classifier, accuracy, f1_score, log_loss};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scoring {
+    NegLogLoss,
+    Accuracy,
+    F1, // positive class = 1
+}
+
+// K-fold over contiguous test blocks. Training samples whose label span overlaps the test
+// block are purged, and those starting within `embargo` bars after it are dropped.
+// spans[i] = (first bar, last bar) of label i, with samples in chronological order.
+pub fn purged_kfold(spans: &[(usize, usize)], n_splits: usize, embargo: usize) -> Vec<(Vec<usize>, Vec<usize>)> {
+    let n = spans.len();
+    let n_splits = n_splits.clamp(2, n.max(2));
+    let mut folds = Vec::with_capacity(n_splits);
+
+    for k in 0..n_splits {
+        let start = k * n / n_splits;
+        let end = (k + 1) * n / n_splits;
+        if start >= end {
+            continue;
+        }
+        let test: Vec<usize> = (start..end).collect();
+        let test_t0 = spans[start..end].iter().map(|s| s.0).min().unwrap();
+        let test_t1 = spans[start..end].iter().map(|s| s.1).max().unwrap() + embargo;
+
+        let train: Vec<usize> = (0..n)
+            .filter(|&i| i < start || i >= end)
+            .filter(|&i| spans[i].1 < test_t0 || spans[i].0 > test_t1)
+            .collect();
+        folds.push((train, test));
+    }
+    folds
+}
+
+pub fn select_rows(x: &[Vec<f64>], idx: &[usize]) -> Vec<Vec<f64>> {
+    idx.iter().map(|&i| x[i].clone()).collect()
+}
+
+pub fn select<T: Copy>(v: &[T], idx: &[usize]) -> Vec<T> {
+    idx.iter().map(|&i| v[i]).collect()
+}
+
+// Score of a fitted model on a test set (higher is better)
+pub fn score<C: Classifier>(model: &C, x: &[Vec<f64>], y: &[i32], w: &[f64], scoring: Scoring) -> f64 {
+    match scoring {
+        Scoring::NegLogLoss => -log_loss(y, &model.predict_proba(x), model.classes(), w),
+        Scoring::Accuracy => accuracy(y, &model.predict(x), w),
+        Scoring::F1 => f1_score(y, &model.predict(x), 1, w),
+    }
+}
+
+// Score of a fresh clone of `model` on every fold, weighting both fit and score by `w`
+pub fn cv_score<C: Classifier + Clone>(
+    model: &C,
+    x: &[Vec<f64>],
+    y: &[i32],
+    w: &[f64],
+    folds: &[(Vec<usize>, Vec<usize>)],
+    scoring: Scoring,
+) -> Vec<f64> {
+    folds.iter().map(|(train, test)| {
+        let mut fitted = model.clone();
+        fitted.fit(&select_rows(x, train), &select(y, train), &select(w, train));
+        score(&fitted, &select_rows(x, test), &select(y, test), &select(w, test), scoring)
+    }).collect()
+}
@@ -0,0 +1 @@
+pub mod cross_validation;
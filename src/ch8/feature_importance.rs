@@ -0,0 +1,345 @@
+use crate::config;
+use crate::base::read_trades;
+use crate::base::linalg::{covariance_matrix, symmetric_eigen};
+use crate::ch2::bar_builder::dollar_metric;
+use crate::ch2::dollar_bar::compute_dollar_bars;
+use crate::ch6::classifier::{Classifier, DecisionTree};
+use crate::ch6::ensemble::{ForestClassWeight, RandomForest};
+use crate::ch6::features::{FEATURE_NAMES, build_dataset, label_uniqueness};
+use crate::ch7::cross_validation::{Scoring, cv_score, purged_kfold, score, select, select_rows};
+use std::error::Error;
+use plotters::prelude::*;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+
+#[derive(Debug, Clone)]
+pub struct Importance {
+    pub name: String,
+    pub mean: f64,
+    pub std: f64, // standard error of the mean
+}
+
+fn mean_and_std_error(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    if n == 0.0 {
+        return (f64::NAN, f64::NAN);
+    }
+    let mean = values.iter().sum::<f64>() / n;
+    if n < 2.0 {
+        return (mean, 0.0);
+    }
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, (variance / n).sqrt())
+}
+
+// Sorted from most to least important
+fn ranked(names: &[String], stats: Vec<(f64, f64)>) -> Vec<Importance> {
+    let mut importances: Vec<Importance> = names.iter().zip(stats)
+        .map(|(name, (mean, std))| Importance { name: name.clone(), mean, std })
+        .collect();
+    importances.sort_by(|a, b| b.mean.total_cmp(&a.mean));
+    importances
+}
+
+// Mean decrease impurity across the trees of an ensemble.
+// A feature never used by a tree counts as missing rather than 0 (AFML uses max_features = 1),
+// and the means are normalized to sum to 1.
+pub fn mdi_importance(trees: &[DecisionTree], names: &[String]) -> Vec<Importance> {
+    let per_tree: Vec<Vec<f64>> = trees.iter().map(|t| t.feature_importances()).collect();
+    let mut stats: Vec<(f64, f64)> = (0..names.len()).map(|f| {
+        let used: Vec<f64> = per_tree.iter().map(|imp| imp[f]).filter(|v| *v > 0.0).collect();
+        let (mean, std) = mean_and_std_error(&used);
+        if mean.is_nan() { (0.0, 0.0) } else { (mean, std) }
+    }).collect();
+
+    let total: f64 = stats.iter().map(|(m, _)| m).sum();
+    if total > 0.0 {
+        stats.iter_mut().for_each(|(m, s)| { *m /= total; *s /= total; });
+    }
+    ranked(names, stats)
+}
+
+// Mean decrease accuracy: out-of-fold loss of score when one feature column is shuffled,
+// relative to the maximum possible improvement
+#[allow(clippy::too_many_arguments)]
+pub fn mda_importance<C: Classifier + Clone>(
+    model: &C,
+    x: &[Vec<f64>],
+    y: &[i32],
+    w: &[f64],
+    folds: &[(Vec<usize>, Vec<usize>)],
+    scoring: Scoring,
+    names: &[String],
+    seed: u64,
+) -> Vec<Importance> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut per_fold: Vec<Vec<f64>> = vec![Vec::new(); names.len()];
+
+    for (train, test) in folds {
+        let mut fitted = model.clone();
+        fitted.fit(&select_rows(x, train), &select(y, train), &select(w, train));
+
+        let x_test = select_rows(x, test);
+        let y_test = select(y, test);
+        let w_test = select(w, test);
+        let base = score(&fitted, &x_test, &y_test, &w_test, scoring);
+
+        for (f, fold_imp) in per_fold.iter_mut().enumerate() {
+            let mut column: Vec<f64> = x_test.iter().map(|r| r[f]).collect();
+            column.shuffle(&mut rng);
+            let mut shuffled = x_test.clone();
+            for (row, v) in shuffled.iter_mut().zip(column) {
+                row[f] = v;
+            }
+            let permuted = score(&fitted, &shuffled, &y_test, &w_test, scoring);
+
+            let denom = match scoring {
+                Scoring::NegLogLoss => -permuted,
+                Scoring::Accuracy | Scoring::F1 => 1.0 - permuted,
+            };
+            fold_imp.push(if denom != 0.0 { (base - permuted) / denom } else { 0.0 });
+        }
+    }
+
+    ranked(names, per_fold.iter().map(|v| mean_and_std_error(v)).collect())
+}
+
+// Single feature importance: out-of-fold score of a model trained on each feature alone
+pub fn sfi_importance<C: Classifier + Clone>(
+    model: &C,
+    x: &[Vec<f64>],
+    y: &[i32],
+    w: &[f64],
+    folds: &[(Vec<usize>, Vec<usize>)],
+    scoring: Scoring,
+    names: &[String],
+) -> Vec<Importance> {
+    let stats = (0..names.len()).map(|f| {
+        let column: Vec<Vec<f64>> = x.iter().map(|r| vec![r[f]]).collect();
+        mean_and_std_error(&cv_score(model, &column, y, w, folds, scoring))
+    }).collect();
+    ranked(names, stats)
+}
+
+// Principal components of the standardized features explaining `variance_threshold`
+// of the variance. Returns the projected features and the kept eigenvalues.
+pub fn orthogonal_features(x: &[Vec<f64>], variance_threshold: f64) -> (Vec<Vec<f64>>, Vec<f64>) {
+    let m = x.first().map(|r| r.len()).unwrap_or(0);
+    let n = x.len() as f64;
+    let means: Vec<f64> = (0..m).map(|j| x.iter().map(|r| r[j]).sum::<f64>() / n).collect();
+    let stds: Vec<f64> = (0..m).map(|j| {
+        let var = x.iter().map(|r| (r[j] - means[j]).powi(2)).sum::<f64>() / (n - 1.0);
+        if var > 0.0 { var.sqrt() } else { 1.0 }
+    }).collect();
+    let z: Vec<Vec<f64>> = x.iter()
+        .map(|r| (0..m).map(|j| (r[j] - means[j]) / stds[j]).collect())
+        .collect();
+
+    let (values, vectors) = symmetric_eigen(&covariance_matrix(&z));
+    let total: f64 = values.iter().sum();
+    let mut keep = 0;
+    let mut cumulative = 0.0;
+    for v in &values {
+        keep += 1;
+        cumulative += v;
+        if cumulative / total >= variance_threshold {
+            break;
+        }
+    }
+
+    let projected = z.iter()
+        .map(|r| (0..keep).map(|k| (0..m).map(|j| r[j] * vectors[j][k]).sum()).collect())
+        .collect();
+    (projected, values[..keep].to_vec())
+}
+
+// Weighted Kendall's tau with additive hyperbolic weights 1/(r+1), where r ranks by decreasing
+// value; averaged over ranking by x and by y
+pub fn weighted_kendall_tau(x: &[f64], y: &[f64]) -> f64 {
+    let n = x.len();
+    let sign = |d: f64| if d > 0.0 { 1.0 } else if d < 0.0 { -1.0 } else { 0.0 };
+    let tau_by = |key: &[f64]| -> f64 {
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&a, &b| key[b].total_cmp(&key[a]));
+        let mut rank = vec![0; n];
+        for (r, &i) in order.iter().enumerate() {
+            rank[i] = r;
+        }
+
+        let (mut concordance, mut sxx, mut syy) = (0.0, 0.0, 0.0);
+        for i in 0..n {
+            for j in i + 1..n {
+                let w = 1.0 / (rank[i] + 1) as f64 + 1.0 / (rank[j] + 1) as f64;
+                let sx = sign(x[i] - x[j]);
+                let sy = sign(y[i] - y[j]);
+                concordance += w * sx * sy;
+                sxx += w * sx * sx;
+                syy += w * sy * sy;
+            }
+        }
+        if sxx > 0.0 && syy > 0.0 { concordance / (sxx * syy).sqrt() } else { 0.0 }
+    };
+    (tau_by(x) + tau_by(y)) / 2.0
+}
+
+pub fn write_importance_csv(importances: &[Importance], path: &str) -> Result<(), Box<dyn Error>> {
+    let mut wtr = csv::Writer::from_path(path)?;
+    wtr.write_record(["rank", "feature", "mean", "std"])?;
+    for (rank, imp) in importances.iter().enumerate() {
+        wtr.write_record([(rank + 1).to_string(), imp.name.clone(), imp.mean.to_string(), imp.std.to_string()])?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+fn print_importance(title: &str, importances: &[Importance]) {
+    println!("--- {} ---", title);
+    for (rank, imp) in importances.iter().enumerate() {
+        println!("{:>2}. {:<22} {:>9.4} +/- {:.4}", rank + 1, imp.name, imp.mean, imp.std);
+    }
+}
+
+pub fn run_feature_importance() -> Result<(), Box<dyn Error>> {
+    let file_path = config::TARDIS_CSV_PATH;
+    println!("Reading trades from {}...", file_path);
+    let trades = read_trades(file_path)?;
+    println!("Read {} trades.", trades.len());
+
+    // 1. Compute Dollar Bars (~1000 bars) and the ch6 dataset
+    let total_dollar: f64 = trades.iter().map(dollar_metric).sum();
+    let bars = compute_dollar_bars(&trades, total_dollar / 1000.0);
+    println!("Generated {} dollar bars.", bars.len());
+
+    let dataset = build_dataset(&bars, 5);
+    if dataset.x.len() < 50 {
+        return Err("Not enough bars to build a dataset".into());
+    }
+    let weights = label_uniqueness(&dataset.spans);
+    let avg_uniqueness = weights.iter().sum::<f64>() / weights.len() as f64;
+    let names: Vec<String> = FEATURE_NAMES.iter().map(|s| s.to_string()).collect();
+
+    // 2. Purged 5-fold CV with a 1% embargo
+    let embargo = (bars.len() as f64 * 0.01).ceil() as usize;
+    let folds = purged_kfold(&dataset.spans, 5, embargo);
+    println!("Built {} samples, {} purged folds (embargo {} bars).", dataset.x.len(), folds.len(), embargo);
+
+    // max_features = 1 so every feature gets a chance at every split (no masking in MDI)
+    let mut forest = RandomForest::new(200, avg_uniqueness, ForestClassWeight::BalancedSubsample, 42);
+    forest.max_features = Some(1);
+    forest.min_samples_leaf = 5;
+
+    // 3. MDI (in-sample)
+    let mut fitted = forest.clone();
+    fitted.fit(&dataset.x, &dataset.y, &weights);
+    let trees = fitted.bagging.as_ref().map(|b| b.estimators.clone()).unwrap_or_default();
+    let mdi = mdi_importance(&trees, &names);
+    print_importance("MDI", &mdi);
+
+    // 4. MDA and SFI (out-of-sample)
+    let mda = mda_importance(&forest, &dataset.x, &dataset.y, &weights, &folds, Scoring::NegLogLoss, &names, 42);
+    print_importance("MDA (neg log loss)", &mda);
+    let mda_accuracy = mda_importance(&forest, &dataset.x, &dataset.y, &weights, &folds, Scoring::Accuracy, &names, 42);
+    print_importance("MDA (accuracy)", &mda_accuracy);
+    let sfi = sfi_importance(&forest, &dataset.x, &dataset.y, &weights, &folds, Scoring::NegLogLoss, &names);
+    print_importance("SFI (neg log loss)", &sfi);
+
+    // 5. Orthogonal features: MDI on principal components vs their PCA rank
+    let (pcs, eigenvalues) = orthogonal_features(&dataset.x, 0.95);
+    let pc_names: Vec<String> = (1..=eigenvalues.len()).map(|k| format!("PC_{}", k)).collect();
+    let mut pc_forest = forest.clone();
+    pc_forest.fit(&pcs, &dataset.y, &weights);
+    let pc_trees = pc_forest.bagging.as_ref().map(|b| b.estimators.clone()).unwrap_or_default();
+    let pc_mdi = mdi_importance(&pc_trees, &pc_names);
+    print_importance("MDI on orthogonal features", &pc_mdi);
+
+    let importance_by_pc: Vec<f64> = pc_names.iter()
+        .map(|name| pc_mdi.iter().find(|imp| &imp.name == name).map(|imp| imp.mean).unwrap_or(0.0))
+        .collect();
+    let inverse_pca_rank: Vec<f64> = (1..=eigenvalues.len()).map(|k| 1.0 / k as f64).collect();
+    println!(
+        "{} components explain 95% of variance; weighted Kendall's tau (MDI vs PCA rank) = {:.4}",
+        eigenvalues.len(),
+        weighted_kendall_tau(&importance_by_pc, &inverse_pca_rank)
+    );
+
+    // 6. Save tables and charts
+    std::fs::create_dir_all("src/ch8/result")?;
+    let outputs = [
+        ("MDI", "mdi", &mdi),
+        ("MDA", "mda", &mda),
+        ("MDA (Accuracy)", "mda_accuracy", &mda_accuracy),
+        ("SFI", "sfi", &sfi),
+        ("MDI (Orthogonal Features)", "mdi_orthogonal", &pc_mdi),
+    ];
+    for (title, file, importances) in outputs {
+        let csv_path = format!("src/ch8/result/{}.csv", file);
+        let png_path = format!("src/ch8/result/{}.png", file);
+        write_importance_csv(importances, &csv_path)?;
+        draw_importance_chart(&format!("Feature Importance: {}", title), importances, &png_path)?;
+        println!("Saved {} and {}", csv_path, png_path);
+    }
+
+    let output_path = "src/ch8/result/mda.png";
+
+    // Open file
+    #[cfg(target_os = "windows")]
+    std::process::Command::new("cmd")
+        .args(["/C", "start", output_path])
+        .spawn()?;
+
+    #[cfg(target_os = "macos")]
+    std::process::Command::new("open")
+        .arg(output_path)
+        .spawn()?;
+
+    #[cfg(target_os = "linux")]
+    std::process::Command::new("xdg-open")
+        .arg(output_path)
+        .spawn()?;
+
+    Ok(())
+}
+
+// Horizontal bars with standard-error whiskers, most important at the top
+fn draw_importance_chart(title: &str, importances: &[Importance], output_path: &str) -> Result<(), Box<dyn Error>> {
+    let root = BitMapBackend::new(output_path, (1024, 768)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let n = importances.len();
+    let min_x = importances.iter().map(|i| i.mean - i.std).fold(0.0, f64::min);
+    let max_x = importances.iter().map(|i| i.mean + i.std).fold(0.0, f64::max);
+    let pad = ((max_x - min_x) * 0.05).max(1e-6);
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption(title, ("sans-serif", 30).into_font())
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(180)
+        .build_cartesian_2d((min_x - pad)..(max_x + pad), -0.5..(n as f64 - 0.5))?;
+
+    chart.configure_mesh()
+        .disable_y_mesh()
+        .y_labels(n)
+        .y_label_formatter(&|y| {
+            let pos = y.round();
+            if (y - pos).abs() < 1e-6 && pos >= 0.0 && (pos as usize) < n {
+                importances[n - 1 - pos as usize].name.clone()
+            } else {
+                String::new()
+            }
+        })
+        .draw()?;
+
+    chart.draw_series(importances.iter().enumerate().map(|(i, imp)| {
+        let y = (n - 1 - i) as f64;
+        Rectangle::new([(0.0, y - 0.35), (imp.mean, y + 0.35)], BLUE.mix(0.5).filled())
+    }))?;
+
+    chart.draw_series(importances.iter().enumerate().map(|(i, imp)| {
+        let y = (n - 1 - i) as f64;
+        ErrorBar::new_horizontal(y, imp.mean - imp.std, imp.mean, imp.mean + imp.std, BLACK.filled(), 10)
+    }))?;
+
+    Ok(())
+}
@@ -0,0 +1,3 @@
+pub mod feature_importance;
+
+pub use feature_importance::run_feature_importance;
@@ -0,0 +1,208 @@
+use crate::config;
+use crate::base::read_trades;
+use crate::ch2::bar_builder::dollar_metric;
+use crate::ch2::dollar_bar::compute_dollar_bars;
+use crate::ch6::classifier::Classifier;
+use crate::ch6::ensemble::{ForestClassWeight, RandomForest};
+use crate::ch6::features::{build_dataset, label_uniqueness};
+use crate::ch7::cross_validation::{Scoring, cv_score, purged_kfold};
+use std::error::Error;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use rayon::prelude::*;
+
+// One hyperparameter combination: (name, value) pairs, integers stored as f64
+pub type ParamSet = Vec<(String, f64)>;
+
+#[derive(Debug, Clone)]
+pub enum ParamDistribution {
+    Choice(Vec<f64>),
+    Uniform(f64, f64),
+    LogUniform(f64, f64), // log(value) uniform on [log(a), log(b)], a > 0
+}
+
+impl ParamDistribution {
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> f64 {
+        match self {
+            ParamDistribution::Choice(values) => values[rng.gen_range(0..values.len())],
+            ParamDistribution::Uniform(a, b) => rng.gen_range(*a..*b),
+            ParamDistribution::LogUniform(a, b) => rng.gen_range(a.ln()..b.ln()).exp(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub params: ParamSet,
+    pub mean_score: f64,
+    pub std_score: f64,
+    pub fold_scores: Vec<f64>,
+}
+
+// Every combination of the grid values
+pub fn grid_candidates(grid: &[(&str, Vec<f64>)]) -> Vec<ParamSet> {
+    let mut candidates: Vec<ParamSet> = vec![Vec::new()];
+    for (name, values) in grid {
+        candidates = candidates.into_iter()
+            .flat_map(|set| values.iter().map(move |v| {
+                let mut next = set.clone();
+                next.push((name.to_string(), *v));
+                next
+            }))
+            .collect();
+    }
+    candidates
+}
+
+// n_iter independent draws from the distributions
+pub fn random_candidates(distributions: &[(&str, ParamDistribution)], n_iter: usize, seed: u64) -> Vec<ParamSet> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..n_iter)
+        .map(|_| distributions.iter().map(|(name, d)| (name.to_string(), d.sample(&mut rng))).collect())
+        .collect()
+}
+
+pub fn param(params: &ParamSet, name: &str) -> Option<f64> {
+    params.iter().find(|(n, _)| n == name).map(|(_, v)| *v)
+}
+
+// Cross-validate every candidate in parallel; best first
+pub fn search<C, F>(
+    build: F,
+    candidates: Vec<ParamSet>,
+    x: &[Vec<f64>],
+    y: &[i32],
+    w: &[f64],
+    folds: &[(Vec<usize>, Vec<usize>)],
+    scoring: Scoring,
+) -> Vec<SearchResult>
+where
+    C: Classifier + Clone,
+    F: Fn(&ParamSet) -> C + Sync,
+{
+    let mut results: Vec<SearchResult> = candidates.into_par_iter().map(|params| {
+        let model = build(&params);
+        let fold_scores = cv_score(&model, x, y, w, folds, scoring);
+        let n = fold_scores.len() as f64;
+        let mean_score = fold_scores.iter().sum::<f64>() / n;
+        let std_score = if n > 1.0 {
+            (fold_scores.iter().map(|s| (s - mean_score).powi(2)).sum::<f64>() / (n - 1.0)).sqrt()
+        } else {
+            0.0
+        };
+        SearchResult { params, mean_score, std_score, fold_scores }
+    }).collect();
+
+    results.sort_by(|a, b| b.mean_score.total_cmp(&a.mean_score));
+    results
+}
+
+pub fn write_leaderboard_csv(results: &[SearchResult], path: &str) -> Result<(), Box<dyn Error>> {
+    let mut wtr = csv::Writer::from_path(path)?;
+    let Some(first) = results.first() else {
+        wtr.flush()?;
+        return Ok(());
+    };
+
+    let mut header = vec!["rank".to_string(), "mean_score".to_string(), "std_score".to_string()];
+    header.extend(first.params.iter().map(|(name, _)| name.clone()));
+    header.extend((1..=first.fold_scores.len()).map(|k| format!("fold_{}", k)));
+    wtr.write_record(&header)?;
+
+    for (rank, result) in results.iter().enumerate() {
+        let mut record = vec![(rank + 1).to_string(), result.mean_score.to_string(), result.std_score.to_string()];
+        record.extend(result.params.iter().map(|(_, v)| v.to_string()));
+        record.extend(result.fold_scores.iter().map(|s| s.to_string()));
+        wtr.write_record(&record)?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+// Random forest from a parameter set; max_depth = 0 means unlimited
+fn build_forest(params: &ParamSet) -> RandomForest {
+    let mut forest = RandomForest::new(
+        param(params, "n_estimators").unwrap_or(100.0) as usize,
+        param(params, "max_samples").unwrap_or(1.0),
+        ForestClassWeight::BalancedSubsample,
+        42,
+    );
+    forest.min_samples_leaf = param(params, "min_samples_leaf").unwrap_or(1.0).round().max(1.0) as usize;
+    forest.max_depth = param(params, "max_depth").map(|d| d as usize).filter(|d| *d > 0);
+    forest
+}
+
+fn print_leaderboard(title: &str, results: &[SearchResult], top: usize) {
+    println!("--- {} (top {}) ---", title, top.min(results.len()));
+    for (rank, result) in results.iter().take(top).enumerate() {
+        let params: Vec<String> = result.params.iter().map(|(n, v)| {
+            if v.fract() == 0.0 { format!("{}={}", n, v) } else { format!("{}={:.4}", n, v) }
+        }).collect();
+        println!("{:>2}. {:.4} +/- {:.4}  {}", rank + 1, result.mean_score, result.std_score, params.join(", "));
+    }
+}
+
+pub fn run_hyperparameter_tuning(scoring_name: &str) -> Result<(), Box<dyn Error>> {
+    let scoring = match scoring_name {
+        "neg_log_loss" => Scoring::NegLogLoss,
+        "f1" => Scoring::F1,
+        _ => return Err(format!("Unknown scoring: {} (use neg_log_loss or f1)", scoring_name).into()),
+    };
+
+    let file_path = config::TARDIS_CSV_PATH;
+    println!("Reading trades from {}...", file_path);
+    let trades = read_trades(file_path)?;
+    println!("Read {} trades.", trades.len());
+
+    // 1. Dataset from ~1000 dollar bars, uniqueness weights, purged folds
+    let total_dollar: f64 = trades.iter().map(dollar_metric).sum();
+    let bars = compute_dollar_bars(&trades, total_dollar / 1000.0);
+    println!("Generated {} dollar bars.", bars.len());
+
+    let dataset = build_dataset(&bars, 5);
+    if dataset.x.len() < 50 {
+        return Err("Not enough bars to build a dataset".into());
+    }
+    let weights = label_uniqueness(&dataset.spans);
+    let avg_uniqueness = weights.iter().sum::<f64>() / weights.len() as f64;
+    let embargo = (bars.len() as f64 * 0.01).ceil() as usize;
+    let folds = purged_kfold(&dataset.spans, 5, embargo);
+    println!(
+        "Built {} samples, {} purged folds, scoring = {}, {} threads.",
+        dataset.x.len(), folds.len(), scoring_name, rayon::current_num_threads()
+    );
+
+    // 2. Grid search
+    let grid = [
+        ("n_estimators", vec![50.0, 100.0]),
+        ("max_depth", vec![3.0, 6.0, 0.0]),
+        ("min_samples_leaf", vec![1.0, 5.0, 20.0]),
+        ("max_samples", vec![avg_uniqueness, 1.0]),
+    ];
+    let candidates = grid_candidates(&grid);
+    println!("Grid search over {} candidates...", candidates.len());
+    let grid_results = search(build_forest, candidates, &dataset.x, &dataset.y, &weights, &folds, scoring);
+    print_leaderboard("Grid Search", &grid_results, 5);
+
+    // 3. Randomized search with log-uniform draws
+    let distributions = [
+        ("n_estimators", ParamDistribution::Choice(vec![50.0, 100.0])),
+        ("max_depth", ParamDistribution::Choice(vec![2.0, 4.0, 8.0, 0.0])),
+        ("min_samples_leaf", ParamDistribution::LogUniform(1.0, 100.0)),
+        ("max_samples", ParamDistribution::Uniform(avg_uniqueness.min(0.99), 1.0)),
+    ];
+    let candidates = random_candidates(&distributions, 25, 42);
+    println!("Randomized search over {} candidates...", candidates.len());
+    let random_results = search(build_forest, candidates, &dataset.x, &dataset.y, &weights, &folds, scoring);
+    print_leaderboard("Randomized Search", &random_results, 5);
+
+    // 4. Save leaderboards
+    std::fs::create_dir_all("src/ch9/result")?;
+    let grid_path = format!("src/ch9/result/grid_search_{}.csv", scoring_name);
+    let random_path = format!("src/ch9/result/random_search_{}.csv", scoring_name);
+    write_leaderboard_csv(&grid_results, &grid_path)?;
+    write_leaderboard_csv(&random_results, &random_path)?;
+    println!("Leaderboards saved to {} and {}", grid_path, random_path);
+
+    Ok(())
+}
@@ -0,0 +1,3 @@
+pub mod hyperparameter_tuning;
+
+pub use hyperparameter_tuning::run_hyperparameter_tuning;
@@ -1 +1,19 @@
-pub const TARDIS_CSV_PATH: &str = "data/2024-06-26_BTCUSDT_trades.csv";
\ No newline at end of file
+pub const TARDIS_CSV_PATH: &str = "data/2024-06-26_BTCUSDT_trades.csv";
+
+// Tardis incremental_book_L2, book_snapshot_25 and quotes datasets for the same day; book
+// features and trade classification use the first one present and fail when none exists
+// (book_features_demo runs on synthetic books instead)
+pub const TARDIS_L2_PATH: &str = "data/2024-06-26_BTCUSDT_incremental_book_L2.csv";
+pub const TARDIS_BOOK_PATH: &str = "data/2024-06-26_BTCUSDT_book_snapshot_25.csv";
+pub const TARDIS_QUOTES_PATH: &str = "data/2024-06-26_BTCUSDT_quotes.csv";
+
+// Dated futures contracts for the continuous series: (symbol, expiry RFC 3339, trades CSV, open interest CSV or "")
+pub const FUTURES_CONTRACTS: &[(&str, &str, &str, &str)] = &[];
+
+// Instrument metadata (contract type, multiplier, tick/lot size, currencies) keyed by exchange / symbol
+pub const INSTRUMENTS_PATH: &str = "src/config/instruments.json";
+
+// Currency the dollar metric is expressed in, and rate series used to convert other
+// quote / settlement currencies into it: (base, quote, CSV with timestamp and rate columns)
+pub const REFERENCE_CURRENCY: &str = "USDT";
+pub const FX_SERIES: &[(&str, &str, &str)] = &[];
@@ -3,6 +3,17 @@ use std::env;
 mod config;
 pub mod base;
 mod ch2;
+mod ch3;
+mod ch6;
+mod ch7;
+mod ch8;
+mod ch9;
+mod ch10;
+mod ch11;
+mod ch13;
+mod ch14;
+mod ch15;
+mod backtest;
 
 fn main() {
     let args: Vec<String> = env::args().collect();
@@ -39,6 +50,12 @@ fn main() {
                 eprintln!("Error in Dollar Bar: {}", e);
             }
         }
+        "dynamic_dollar" => {
+            println!("\n--- Dynamic Dollar Bar ---");
+            if let Err(e) = ch2::draw_dynamic_dollar_bar() {
+                eprintln!("Error in Dynamic Dollar Bar: {}", e);
+            }
+        }
         "imbalance" => {
             println!("\n--- Tick Imbalance Bar ---");
             if let Err(e) = ch2::draw_tick_imbalance_bar() {
@@ -57,18 +74,153 @@ fn main() {
                 eprintln!("Error in Dollar Imbalance Bar: {}", e);
             }
         }
+        "run" => {
+            println!("\n--- Run Bar ---");
+            if let Err(e) = ch2::draw_run_bar() {
+                eprintln!("Error in Run Bar: {}", e);
+            }
+        }
+        "range" => {
+            println!("\n--- Range Bar ---");
+            if let Err(e) = ch2::draw_range_bar() {
+                eprintln!("Error in Range Bar: {}", e);
+            }
+        }
+        "renko" => {
+            println!("\n--- Renko Bar ---");
+            if let Err(e) = ch2::draw_renko_bar() {
+                eprintln!("Error in Renko Bar: {}", e);
+            }
+        }
+        "volatility_bar" => {
+            println!("\n--- Volatility Bar ---");
+            if let Err(e) = ch2::draw_volatility_bar() {
+                eprintln!("Error in Volatility Bar: {}", e);
+            }
+        }
         "cusum" => {
             println!("\n--- CUSUM Filter ---");
             if let Err(e) = ch2::draw_cusum_filter() {
                 eprintln!("Error in CUSUM Filter: {}", e);
             }
         }
+        "roll" => {
+            println!("\n--- Continuous Futures ---");
+            if let Err(e) = ch2::run_futures_roll() {
+                eprintln!("Error in Continuous Futures: {}", e);
+            }
+        }
+        "etf_trick" => {
+            println!("\n--- ETF Trick ---");
+            if let Err(e) = ch2::run_etf_trick() {
+                eprintln!("Error in ETF Trick: {}", e);
+            }
+        }
+        "pca_weights" => {
+            println!("\n--- PCA Weights ---");
+            if let Err(e) = ch2::run_pca_weights() {
+                eprintln!("Error in PCA Weights: {}", e);
+            }
+        }
+        "book_features" => {
+            println!("\n--- Book Features ---");
+            if let Err(e) = ch2::run_book_features() {
+                eprintln!("Error in Book Features: {}", e);
+            }
+        }
+        "book_features_demo" => {
+            println!("\n--- Book Features (synthetic books) ---");
+            if let Err(e) = ch2::run_book_features_demo() {
+                eprintln!("Error in Book Features: {}", e);
+            }
+        }
+        "classify_trades" => {
+            println!("\n--- Trade Classification ---");
+            if let Err(e) = ch2::run_trade_classification() {
+                eprintln!("Error in Trade Classification: {}", e);
+            }
+        }
+        "calibrate" => {
+            println!("\n--- Bar Calibration ---");
+            if let Err(e) = ch2::run_calibration() {
+                eprintln!("Error in Bar Calibration: {}", e);
+            }
+        }
         "compare" => {
             println!("\n--- Compare Distribution ---");
             if let Err(e) = ch2::run_compare_distribution() {
                 eprintln!("Error in Compare Distribution: {}", e);
             }
         }
+        "volatility" => {
+            println!("\n--- Volatility Estimators ---");
+            if let Err(e) = ch3::run_volatility() {
+                eprintln!("Error in Volatility Estimators: {}", e);
+            }
+        }
+        "bar_quality" => {
+            println!("\n--- Bar Quality ---");
+            if let Err(e) = ch2::run_bar_quality() {
+                eprintln!("Error in Bar Quality: {}", e);
+            }
+        }
+        "ensemble" => {
+            println!("\n--- Ensemble Methods ---");
+            if let Err(e) = ch6::run_ensemble_methods() {
+                eprintln!("Error in Ensemble Methods: {}", e);
+            }
+        }
+        "feature_importance" => {
+            println!("\n--- Feature Importance ---");
+            if let Err(e) = ch8::run_feature_importance() {
+                eprintln!("Error in Feature Importance: {}", e);
+            }
+        }
+        "tuning" => {
+            println!("\n--- Hyperparameter Tuning ---");
+            let scoring = args.get(2).map(|s| s.as_str()).unwrap_or("neg_log_loss");
+            if let Err(e) = ch9::run_hyperparameter_tuning(scoring) {
+                eprintln!("Error in Hyperparameter Tuning: {}", e);
+            }
+        }
+        "bet_sizing" => {
+            println!("\n--- Bet Sizing ---");
+            if let Err(e) = ch10::run_bet_sizing() {
+                eprintln!("Error in Bet Sizing: {}", e);
+            }
+        }
+        "backtest" => {
+            println!("\n--- Backtest ---");
+            if let Err(e) = backtest::run_backtest() {
+                eprintln!("Error in Backtest: {}", e);
+            }
+        }
+        "pbo" => {
+            println!("\n--- Backtest Overfitting ---");
+            if let Err(e) = ch11::run_backtest_overfitting() {
+                eprintln!("Error in Backtest Overfitting: {}", e);
+            }
+        }
+        "backtest_stats" => {
+            println!("\n--- Backtest Statistics ---");
+            if let Err(e) = ch14::run_backtest_statistics() {
+                eprintln!("Error in Backtest Statistics: {}", e);
+            }
+        }
+        "synthetic" => {
+            println!("\n--- Synthetic Data ---");
+            if let Err(e) = ch13::run_synthetic_data() {
+                eprintln!("Error in Synthetic Data: {}", e);
+            }
+        }
+        "strategy_risk" => {
+            println!("\n--- Strategy Risk ---");
+            let bar_type = args.get(2).map(|s| s.as_str()).unwrap_or("time");
+            let threshold = args.get(3).and_then(|s| s.parse::<f64>().ok());
+            if let Err(e) = ch15::run_strategy_risk(bar_type, threshold) {
+                eprintln!("Error in Strategy Risk: {}", e);
+            }
+        }
         "all" => {
             run_all();
         }
@@ -102,6 +254,11 @@ fn run_all() {
         eprintln!("Error in Dollar Bar: {}", e);
     }
 
+    println!("\n--- Dynamic Dollar Bar ---");
+    if let Err(e) = ch2::draw_dynamic_dollar_bar() {
+        eprintln!("Error in Dynamic Dollar Bar: {}", e);
+    }
+
     println!("\n--- Tick Imbalance Bar ---");
     if let Err(e) = ch2::draw_tick_imbalance_bar() {
         eprintln!("Error in Tick Imbalance Bar: {}", e);
@@ -117,10 +274,120 @@ fn run_all() {
         eprintln!("Error in Dollar Imbalance Bar: {}", e);
     }
 
+    println!("\n--- Run Bar ---");
+    if let Err(e) = ch2::draw_run_bar() {
+        eprintln!("Error in Run Bar: {}", e);
+    }
+
+    println!("\n--- Range Bar ---");
+    if let Err(e) = ch2::draw_range_bar() {
+        eprintln!("Error in Range Bar: {}", e);
+    }
+
+    println!("\n--- Renko Bar ---");
+    if let Err(e) = ch2::draw_renko_bar() {
+        eprintln!("Error in Renko Bar: {}", e);
+    }
+
+    println!("\n--- Volatility Bar ---");
+    if let Err(e) = ch2::draw_volatility_bar() {
+        eprintln!("Error in Volatility Bar: {}", e);
+    }
+
+    println!("\n--- Continuous Futures ---");
+    if let Err(e) = ch2::run_futures_roll() {
+        eprintln!("Error in Continuous Futures: {}", e);
+    }
+
+    println!("\n--- ETF Trick ---");
+    if let Err(e) = ch2::run_etf_trick() {
+        eprintln!("Error in ETF Trick: {}", e);
+    }
+
+    println!("\n--- PCA Weights ---");
+    if let Err(e) = ch2::run_pca_weights() {
+        eprintln!("Error in PCA Weights: {}", e);
+    }
+
+    println!("\n--- Book Features ---");
+    if let Err(e) = ch2::run_book_features() {
+        eprintln!("Error in Book Features: {}", e);
+    }
+
+    println!("\n--- Book Features (synthetic books) ---");
+    if let Err(e) = ch2::run_book_features_demo() {
+        eprintln!("Error in Book Features: {}", e);
+    }
+
+    println!("\n--- Trade Classification ---");
+    if let Err(e) = ch2::run_trade_classification() {
+        eprintln!("Error in Trade Classification: {}", e);
+    }
+
+    println!("\n--- Bar Calibration ---");
+    if let Err(e) = ch2::run_calibration() {
+        eprintln!("Error in Bar Calibration: {}", e);
+    }
+
     println!("\n--- Compare Distribution ---");
     if let Err(e) = ch2::run_compare_distribution() {
         eprintln!("Error in Compare Distribution: {}", e);
     }
+
+    println!("\n--- Bar Quality ---");
+    if let Err(e) = ch2::run_bar_quality() {
+        eprintln!("Error in Bar Quality: {}", e);
+    }
+
+    println!("\n--- Volatility Estimators ---");
+    if let Err(e) = ch3::run_volatility() {
+        eprintln!("Error in Volatility Estimators: {}", e);
+    }
+
+    println!("\n--- Ensemble Methods ---");
+    if let Err(e) = ch6::run_ensemble_methods() {
+        eprintln!("Error in Ensemble Methods: {}", e);
+    }
+
+    println!("\n--- Feature Importance ---");
+    if let Err(e) = ch8::run_feature_importance() {
+        eprintln!("Error in Feature Importance: {}", e);
+    }
+
+    println!("\n--- Hyperparameter Tuning ---");
+    if let Err(e) = ch9::run_hyperparameter_tuning("neg_log_loss") {
+        eprintln!("Error in Hyperparameter Tuning: {}", e);
+    }
+
+    println!("\n--- Bet Sizing ---");
+    if let Err(e) = ch10::run_bet_sizing() {
+        eprintln!("Error in Bet Sizing: {}", e);
+    }
+
+    println!("\n--- Backtest ---");
+    if let Err(e) = backtest::run_backtest() {
+        eprintln!("Error in Backtest: {}", e);
+    }
+
+    println!("\n--- Backtest Overfitting ---");
+    if let Err(e) = ch11::run_backtest_overfitting() {
+        eprintln!("Error in Backtest Overfitting: {}", e);
+    }
+
+    println!("\n--- Backtest Statistics ---");
+    if let Err(e) = ch14::run_backtest_statistics() {
+        eprintln!("Error in Backtest Statistics: {}", e);
+    }
+
+    println!("\n--- Synthetic Data ---");
+    if let Err(e) = ch13::run_synthetic_data() {
+        eprintln!("Error in Synthetic Data: {}", e);
+    }
+
+    println!("\n--- Strategy Risk ---");
+    if let Err(e) = ch15::run_strategy_risk("time", None) {
+        eprintln!("Error in Strategy Risk: {}", e);
+    }
 }
 
 fn print_usage() {
@@ -130,9 +397,32 @@ fn print_usage() {
     println!("  tick       - Generate Tick Bars");
     println!("  volume     - Generate Volume Bars");
     println!("  dollar     - Generate Dollar Bars");
+    println!("  dynamic_dollar    - Generate Dollar Bars with a trailing-window threshold");
     println!("  imbalance         - Generate Tick Imbalance Bars");
     println!("  volume_imbalance  - Generate Volume Imbalance Bars");
     println!("  dollar_imbalance  - Generate Dollar Imbalance Bars");
+    println!("  run               - Generate Dollar Run Bars and custom-metric bars with the generic builder");
+    println!("  range             - Generate Range Bars (fixed and ATR-scaled)");
+    println!("  renko             - Generate Renko Bricks");
+    println!("  volatility_bar    - Generate Volatility Bars (realized variance)");
+    println!("  roll              - Continuous futures from dated contracts (roll rules, gap adjustment)");
+    println!("  etf_trick         - Basket / spread as one total-return series (ETF trick)");
+    println!("  pca_weights       - PCA hedging weights with rolling re-estimation and basket series");
+    println!("  book_features     - Mid, spread, depth imbalance and microprice from book snapshots, aligned to time bars");
+    println!("  book_features_demo - Book features on synthetic books built around the trades (no book data needed)");
+    println!("  classify_trades   - Tick rule, Lee-Ready, EMO and CLNV trade signing against the reported side");
+    println!("  calibrate         - Calibrate tick/volume/dollar/imbalance thresholds to the time-bar frequency");
     println!("  compare           - Compare Distributions");
+    println!("  bar_quality       - Bar-count stability, serial correlation and normality tests per bar type");
+    println!("  volatility        - Close-to-close, EWMA, Parkinson, Garman-Klass, Rogers-Satchell, Yang-Zhang");
+    println!("  ensemble          - Decision tree, bagging and random forest on dollar-bar features");
+    println!("  feature_importance - MDI, MDA, SFI and orthogonal-feature importance");
+    println!("  tuning [neg_log_loss|f1] - Grid and randomized search over random forest hyperparameters");
+    println!("  bet_sizing        - Size bets from predicted probabilities");
+    println!("  backtest          - Backtest bet sizes on time bars");
+    println!("  pbo               - Probability of backtest overfitting (CSCV) over bar type x CUSUM threshold");
+    println!("  backtest_stats    - Backtest statistics report (Sharpe, PSR, DSR, drawdown, ...)");
+    println!("  synthetic         - OU synthetic trades and optimal trading rule heatmap");
+    println!("  strategy_risk [bar_type] [h] - Implied precision/frequency and probability of failure");
     println!("  all        - Run all examples");
 }